@@ -1,3 +1,6 @@
+use crate::config::ConfigFormat;
+use crate::history::{parse_time, RunStatus};
+use chrono::{DateTime, Local};
 use clap::{Parser, Subcommand};
 use std::path::PathBuf;
 
@@ -26,36 +29,102 @@ pub enum Commands {
     Check(CheckArgs),
     Service(ServiceArgs),
     Exec(ExecArgs),
+    History(HistoryArgs),
+    /// Show the state of every task in the running daemon
+    Status(StatusArgs),
+    /// Run a task now inside the running daemon
+    Trigger(TaskControlArgs),
+    /// Stop scheduled runs of a task in the running daemon
+    Pause(TaskControlArgs),
+    /// Resume scheduled runs of a paused task
+    Resume(TaskControlArgs),
+    /// Cancel the in-flight and queued runs of a task
+    Cancel(TaskControlArgs),
+    /// Make the running daemon reload its configuration
+    Reload(ControlArgs),
+}
+
+impl Commands {
+    /// Whether the command prints JSON to stdout, which log lines would
+    /// corrupt.
+    pub fn prints_json(&self) -> bool {
+        match self {
+            Commands::Check(args) => args.json,
+            Commands::History(args) => args.json,
+            Commands::Status(args) => args.json,
+            _ => false,
+        }
+    }
 }
 
 #[derive(clap::Args, Debug)]
 pub struct RunArgs {
     #[arg(short, long)]
     pub config_path: Option<PathBuf>,
+
+    /// Configuration file format (defaults to the file extension)
+    #[arg(long, value_enum)]
+    pub format: Option<ConfigFormat>,
+
+    /// Seconds to wait for running tasks to exit after SIGTERM on shutdown
+    #[arg(short = 'g', long, default_value = "30")]
+    pub shutdown_grace: u64,
+
+    /// Path of the control socket
+    #[arg(short, long)]
+    pub socket: Option<PathBuf>,
 }
 
 #[derive(clap::Args, Debug)]
 pub struct ListArgs {
     #[arg(short, long)]
     pub config_path: Option<PathBuf>,
+
+    /// Configuration file format (defaults to the file extension)
+    #[arg(long, value_enum)]
+    pub format: Option<ConfigFormat>,
+
+    /// Show every setting after `defaults` have been applied
+    #[arg(long)]
+    pub resolved: bool,
 }
 
 #[derive(clap::Args, Debug)]
 pub struct InitArgs {
     #[arg(short, long)]
     pub config_path: Option<PathBuf>,
+
+    /// Configuration file format (defaults to the file extension)
+    #[arg(long, value_enum)]
+    pub format: Option<ConfigFormat>,
 }
 
 #[derive(clap::Args, Debug)]
 pub struct EditArgs {
     #[arg(short, long)]
     pub config_path: Option<PathBuf>,
+
+    /// Configuration file format (defaults to the file extension)
+    #[arg(long, value_enum)]
+    pub format: Option<ConfigFormat>,
 }
 
 #[derive(clap::Args, Debug)]
 pub struct CheckArgs {
     #[arg(short, long)]
     pub config_path: Option<PathBuf>,
+
+    /// Configuration file format (defaults to the file extension)
+    #[arg(long, value_enum)]
+    pub format: Option<ConfigFormat>,
+
+    /// Also lint commands, directories, webhooks and schedules
+    #[arg(long)]
+    pub strict: bool,
+
+    /// Print the lint findings as JSON
+    #[arg(long, requires = "strict")]
+    pub json: bool,
 }
 
 #[derive(clap::Args, Debug)]
@@ -90,4 +159,62 @@ pub struct ExecArgs {
 
     #[arg(short, long)]
     pub config_path: Option<PathBuf>,
+
+    /// Configuration file format (defaults to the file extension)
+    #[arg(long, value_enum)]
+    pub format: Option<ConfigFormat>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct HistoryArgs {
+    /// Only show runs of this task
+    #[arg(long)]
+    pub task: Option<String>,
+
+    /// Only show runs with this status
+    #[arg(short, long, value_enum)]
+    pub status: Option<RunStatus>,
+
+    /// Only show runs started at or after this time
+    #[arg(long, value_parser = parse_time)]
+    pub since: Option<DateTime<Local>>,
+
+    /// Only show runs started at or before this time
+    #[arg(long, value_parser = parse_time)]
+    pub until: Option<DateTime<Local>>,
+
+    /// Number of most recent runs to show (0 for all)
+    #[arg(short = 'n', long, default_value = "20")]
+    pub limit: usize,
+
+    /// Print records as JSON instead of a table
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ControlArgs {
+    /// Path of the control socket
+    #[arg(short, long)]
+    pub socket: Option<PathBuf>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct StatusArgs {
+    /// Path of the control socket
+    #[arg(short, long)]
+    pub socket: Option<PathBuf>,
+
+    /// Print the status as JSON instead of a table
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct TaskControlArgs {
+    pub task_name: String,
+
+    /// Path of the control socket
+    #[arg(short, long)]
+    pub socket: Option<PathBuf>,
 }
@@ -1,10 +1,18 @@
-use crate::cli::{CheckArgs, EditArgs, ExecArgs, InitArgs, ListArgs, RunArgs};
+use crate::cli::{
+    CheckArgs, EditArgs, ExecArgs, HistoryArgs, InitArgs, ListArgs, RunArgs, StatusArgs,
+};
 use crate::cli::{ServiceAction, ServiceArgs};
-use crate::config;
-use crate::config::load_config;
-use crate::scheduler::TaskScheduler;
+use crate::config::{load_config, ConfigFormat, Task};
+use crate::control::{self, ControlCommand, ControlRequest, ControlResponse};
+use crate::history::{self, HistoryFilter, HistoryStore};
+use crate::lint::{self, Severity};
+use crate::notify::{self, Spool};
+use crate::scheduler::{
+    ChildRegistry, RunContext, Runner, TaskScheduler, DEFAULT_KILL_GRACE, DEFAULT_MAX_OUTPUT_BYTES,
+};
 use crate::utils;
 use crate::watcher;
+use chrono::Local;
 use log::{debug, error, info};
 use service_manager::{
     RestartPolicy, ServiceInstallCtx, ServiceLabel, ServiceLevel, ServiceManager, ServiceStartCtx,
@@ -13,10 +21,14 @@ use service_manager::{
 use std::env;
 use std::fs;
 use std::io::{self, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process;
 use std::process::Command;
-use tokio::sync::mpsc;
+use std::time::Duration;
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinHandle;
+use tokio::time;
 use utils::get_config_path;
 
 pub async fn handle_run_command(args: RunArgs) {
@@ -40,7 +52,7 @@ pub async fn handle_run_command(args: RunArgs) {
         process::exit(1);
     }
 
-    match core_check_config(&config_path) {
+    match core_check_config(&config_path, args.format) {
         Ok(_) => {
             info!("Configuration validated successfully.");
         }
@@ -51,24 +63,56 @@ pub async fn handle_run_command(args: RunArgs) {
         }
     }
 
+    let shutdown_grace = Duration::from_secs(args.shutdown_grace);
+    let mut sigterm = match signal(SignalKind::terminate()) {
+        Ok(s) => s,
+        Err(e) => {
+            error!("Failed to install SIGTERM handler: {}", e);
+            process::exit(1);
+        }
+    };
+
     let (tx_reload, mut rx_reload) = mpsc::channel::<()>(1);
+    let (tx_control, mut rx_control) = mpsc::channel::<ControlCommand>(16);
 
-    let mut scheduler = TaskScheduler::new();
+    let socket_path = resolve_socket_path(args.socket);
+    let control_listener = match control::bind(&socket_path).await {
+        Ok(listener) => Some(listener),
+        Err(e) => {
+            error!(
+                "Failed to start control socket at {}: {}",
+                socket_path.display(),
+                e
+            );
+            None
+        }
+    };
+    if let Some(listener) = control_listener {
+        tokio::spawn(control::serve(listener, tx_control));
+    }
+
+    let mut scheduler = TaskScheduler::new(open_history_store(), open_spool());
 
     let watcher_path = config_path.clone();
     let tx_clone = tx_reload.clone();
+    let (tx_env_files, rx_env_files) = watch::channel(Vec::new());
 
     tokio::spawn(async move {
-        if let Err(e) = watcher::start_watcher(&watcher_path, tx_clone).await {
+        if let Err(e) = watcher::start_watcher(&watcher_path, rx_env_files, tx_clone).await {
             error!("Watcher failed: {:?}", e);
         }
     });
 
     info!("chronsync Daemon started.");
 
-    match load_config(&config_path) {
+    let format = args.format;
+    match load_config(&config_path, format) {
         Ok(c) => {
             info!("Configuration loaded. {} tasks.", c.tasks.len());
+            if c.tasks.iter().any(|t| t.login_env) {
+                utils::login_environment();
+            }
+            tx_env_files.send_replace(c.env_files());
             scheduler.reload_tasks(c);
         }
         Err(e) => {
@@ -77,28 +121,95 @@ pub async fn handle_run_command(args: RunArgs) {
         }
     };
 
+    let mut spool_tick = time::interval(notify::SPOOL_RETRY_INTERVAL);
+    let mut spool_retry: Option<JoinHandle<()>> = None;
+
     loop {
         tokio::select! {
+            _ = spool_tick.tick() => {
+                if spool_retry.as_ref().is_none_or(|h| h.is_finished()) {
+                    spool_retry = scheduler.retry_spooled_notifications().map(tokio::spawn);
+                }
+            }
             Some(_) = rx_reload.recv() => {
                 info!("\n>>> CONFIG CHANGE DETECTED! RELOADING... <<<");
-
-                match load_config(&config_path) {
-                    Ok(new_config) => {
-                        scheduler.reload_tasks(new_config);
-                        info!("New configuration applied. Tasks reloaded.");
-                    },
-                    Err(e) => {
-                        error!("Error reloading configuration (Configuration rejected): {}", e);
-                    }
-                }
+                let _ = reload_config(&mut scheduler, &config_path, format, &tx_env_files);
+            }
+            Some(command) = rx_control.recv() => {
+                let response = handle_control_request(&mut scheduler, &config_path, format, &tx_env_files, command.request).await;
+                let _ = command.reply.send(response);
             }
             _ = tokio::signal::ctrl_c() => {
                 info!("\nCtrl+C received. Shutting down gracefully...");
-                scheduler.reload_tasks(config::Config { tasks: vec![] });
+                scheduler.shutdown(shutdown_grace).await;
+                break;
+            }
+            _ = sigterm.recv() => {
+                info!("SIGTERM received. Shutting down gracefully...");
+                scheduler.shutdown(shutdown_grace).await;
                 break;
             }
         }
     }
+
+    let _ = fs::remove_file(&socket_path);
+}
+
+fn reload_config(
+    scheduler: &mut TaskScheduler,
+    config_path: &Path,
+    format: Option<ConfigFormat>,
+    env_files: &watch::Sender<Vec<PathBuf>>,
+) -> Result<String, String> {
+    match load_config(config_path, format) {
+        Ok(new_config) => {
+            let count = new_config.tasks.len();
+            if new_config.tasks.iter().any(|t| t.login_env) {
+                // Captured once; start it now instead of at the first run.
+                tokio::task::spawn_blocking(utils::login_environment);
+            }
+            env_files.send_replace(new_config.env_files());
+            scheduler.reload_tasks(new_config);
+            info!("New configuration applied. Tasks reloaded.");
+            Ok(format!("Configuration reloaded. {} tasks.", count))
+        }
+        Err(e) => {
+            error!(
+                "Error reloading configuration (Configuration rejected): {}",
+                e
+            );
+            Err(format!("Configuration rejected: {}", e))
+        }
+    }
+}
+
+async fn handle_control_request(
+    scheduler: &mut TaskScheduler,
+    config_path: &Path,
+    format: Option<ConfigFormat>,
+    env_files: &watch::Sender<Vec<PathBuf>>,
+    request: ControlRequest,
+) -> ControlResponse {
+    match request {
+        ControlRequest::Status => {
+            let tasks = scheduler.status();
+            let mut response = ControlResponse::ok(format!("{} tasks.", tasks.len()));
+            response.tasks = Some(tasks);
+            response
+        }
+        ControlRequest::Trigger { task } => {
+            ControlResponse::from_result(scheduler.trigger(&task).await)
+        }
+        ControlRequest::Pause { task } => ControlResponse::from_result(scheduler.pause(&task)),
+        ControlRequest::Resume { task } => ControlResponse::from_result(scheduler.resume(&task)),
+        ControlRequest::Cancel { task } => {
+            ControlResponse::from_result(scheduler.cancel(&task).await)
+        }
+        ControlRequest::Reload => {
+            info!("\n>>> RELOAD REQUESTED OVER CONTROL SOCKET <<<");
+            ControlResponse::from_result(reload_config(scheduler, config_path, format, env_files))
+        }
+    }
 }
 
 pub fn handle_list_command(args: ListArgs) {
@@ -123,37 +234,147 @@ pub fn handle_list_command(args: ListArgs) {
         process::exit(1);
     }
 
-    match load_config(&config_path) {
+    match load_config(&config_path, args.format) {
         Ok(config) => {
             println!("Configuration loaded from: {}", config_path.display());
             println!(
                 "\n--- chronsync Task List ({} Tasks) ---",
                 config.tasks.len()
             );
+            let now = Local::now();
             for task in config.tasks {
-                println!("- [{}]: {}\n", task.name, task.cron_schedule.to_string());
+                println!("- [{}]: {}\n", task.name, task.cron_schedule);
+                match (task.next_fire_after(&now), task.timezone) {
+                    (Some(next), Some(tz)) => println!(
+                        "  Next run: {} ({}) / {} (local)",
+                        next.with_timezone(&tz).format("%Y-%m-%d %H:%M:%S %Z"),
+                        tz,
+                        next.format("%Y-%m-%d %H:%M:%S %Z")
+                    ),
+                    (Some(next), None) => println!(
+                        "  Next run: {} (local)",
+                        next.format("%Y-%m-%d %H:%M:%S %Z")
+                    ),
+                    (None, _) => println!("  Next run: -"),
+                }
                 println!(
                     "  Command: {} {:?}",
                     task.command,
-                    task.args.unwrap_or_default()
+                    task.args.as_deref().unwrap_or_default()
                 );
+                if args.resolved {
+                    print_resolved_task(&task);
+                }
                 println!("-----------------------------");
             }
         }
         Err(e) => {
             error!("Error loading configuration: {}", e);
-            error!("The configuration file contains invalid JSON or an invalid cron schedule.");
+            error!("The configuration file could not be parsed or has an invalid cron schedule.");
             process::exit(1);
         }
     }
 }
 
+/// Prints the effective settings of `task` for `list --resolved`.
+fn print_resolved_task(task: &Task) {
+    fn or_dash<T: ToString>(value: Option<T>) -> String {
+        value
+            .map(|v| v.to_string())
+            .unwrap_or_else(|| "-".to_string())
+    }
+
+    println!("  Timezone: {}", or_dash(task.timezone));
+    println!(
+        "  Timeout: {}",
+        or_dash(task.timeout.map(|t| format!("{}s", t)))
+    );
+    println!(
+        "  Kill grace: {}s",
+        task.kill_grace.unwrap_or(DEFAULT_KILL_GRACE)
+    );
+    println!(
+        "  Max output: {} bytes",
+        task.max_output_bytes.unwrap_or(DEFAULT_MAX_OUTPUT_BYTES)
+    );
+    match (&task.log_file, &task.log_rotate) {
+        (Some(file), Some(rotate)) => println!(
+            "  Log file: {} (rotate: {}, keep {})",
+            file.display(),
+            match (rotate.max_bytes, rotate.daily) {
+                (Some(max), true) => format!("daily or at {} bytes", max),
+                (Some(max), false) => format!("at {} bytes", max),
+                (None, true) => "daily".to_string(),
+                (None, false) => "never".to_string(),
+            },
+            rotate.keep
+        ),
+        (Some(file), None) => println!("  Log file: {}", file.display()),
+        (None, _) => println!("  Log file: -"),
+    }
+    println!("  Working directory: {}", or_dash(task.cwd.as_deref()));
+    println!("  Webhook URL: {}", or_dash(task.webhook_url.as_deref()));
+    if !task.notifiers.is_empty() {
+        let names: Vec<&str> = task.notifiers.iter().map(|(n, _)| n.as_str()).collect();
+        println!("  Notifiers: {}", names.join(", "));
+    }
+    if task.has_notify_targets() {
+        let events: Vec<String> = task.notify_events().iter().map(|e| e.to_string()).collect();
+        println!("  Notify on: {}", events.join(", "));
+    }
+    if let Some(throttle) = &task.notify_throttle {
+        let mut limits = Vec::new();
+        if throttle.on_change {
+            limits.push("on change".to_string());
+        }
+        if let Some(max) = throttle.max_alerts {
+            limits.push(format!("at most {} per {}s", max, throttle.window));
+        }
+        if let Some(interval) = throttle.digest_interval {
+            limits.push(format!("digest every {}s", interval));
+        }
+        let limits = (!limits.is_empty()).then(|| limits.join(", "));
+        println!("  Notify throttle: {}", or_dash(limits));
+    }
+    let base_env = match (task.login_env, task.env_clear) {
+        (false, false) => "inherited from daemon".to_string(),
+        (true, false) => "login shell".to_string(),
+        (login, true) => format!(
+            "cleared, inheriting [{}] from {}",
+            task.inherit_env.join(", "),
+            if login { "login shell" } else { "daemon" }
+        ),
+    };
+    println!("  Base environment: {}", base_env);
+    println!("  Overlap: {:?}", task.overlap);
+    println!(
+        "  Retries: {} ({:?}, delay {}s)",
+        task.retries.unwrap_or(0),
+        task.retry_backoff,
+        task.retry_delay(1).as_secs()
+    );
+    match &task.env {
+        Some(env) if !env.is_empty() => {
+            println!("  Env:");
+            let mut vars: Vec<_> = env.iter().collect();
+            vars.sort();
+            for (key, value) in vars {
+                println!("    {}={}", key, value);
+            }
+        }
+        _ => println!("  Env: -"),
+    }
+}
+
 pub fn handle_init_command(args: InitArgs) {
     debug!("Entered handle_init_command with args: {:?}", args);
     let config_path = match args.config_path {
         Some(p) => p,
         None => match get_config_path() {
-            Ok(p) => p,
+            Ok(p) => match args.format {
+                Some(format) => p.with_extension(format.extension()),
+                None => p,
+            },
             Err(e) => {
                 error!("Initialization Error: {}", e);
                 process::exit(1);
@@ -191,26 +412,10 @@ pub fn handle_init_command(args: InitArgs) {
         }
     }
 
-    let initial_config_content = r#"{{
-        "tasks": [
-        {{
-            "name": "sample_ping",
-            "cron_schedule": "*/10 * * * * *",
-            "command": "/bin/sh",
-            "args": [
-                "-c", "/bin/echo \"[Sample] Check at $(date)\""
-            ]
-        }},
-        {{
-            "name": "sample_cleanup",
-            "cron_schedule": "0 0 0 * * *",
-            "command": "usr/bin/find",
-            "args": ["/tmp", "-type", "f", "-atime", "+7", "-delete"]
-        }}
-        ]
-    }}"#;
-
-    fs::write(&config_path, initial_config_content).unwrap_or_else(|e| {
+    let format = ConfigFormat::resolve(&config_path, args.format);
+    debug!("Writing {} template.", format);
+
+    fs::write(&config_path, format.template()).unwrap_or_else(|e| {
         error!(
             "Failed to write configuration file to {}: {}",
             config_path.display(),
@@ -226,7 +431,7 @@ pub fn handle_init_command(args: InitArgs) {
     println!("2. Run the daemon: `chronosync run`");
 }
 
-pub fn core_check_config(config_path: &PathBuf) -> Result<(), String> {
+pub fn core_check_config(config_path: &Path, format: Option<ConfigFormat>) -> Result<(), String> {
     if !config_path.exists() {
         return Err(format!(
             "Configuration file not found at: {}",
@@ -234,7 +439,7 @@ pub fn core_check_config(config_path: &PathBuf) -> Result<(), String> {
         ));
     }
 
-    match load_config(config_path) {
+    match load_config(config_path, format) {
         Ok(config) => {
             info!(
                 "Configuration check successful: {} tasks loaded.",
@@ -242,8 +447,13 @@ pub fn core_check_config(config_path: &PathBuf) -> Result<(), String> {
             );
             Ok(())
         }
+        Err(e) if config_path.is_dir() => Err(format!(
+            "Validation failed: Invalid configuration directory.\n\n{}",
+            e
+        )),
         Err(e) => Err(format!(
-            "Validation failed: Invalid JSON or Cron Schedule.\n  Details: {}",
+            "Validation failed: Invalid {} or Cron Schedule.\n\n{}",
+            ConfigFormat::resolve(config_path, format),
             e
         )),
     }
@@ -295,7 +505,7 @@ pub fn handle_edit_command(args: EditArgs) {
 
     info!("Configuration file edited. The daemon will reload automatically.");
 
-    match core_check_config(&config_path) {
+    match core_check_config(&config_path, args.format) {
         Ok(_) => {
             info!("Configuration saved and validated successfully.");
             info!("The daemon will reload automatically");
@@ -329,15 +539,53 @@ pub fn handle_check_command(args: CheckArgs) {
         process::exit(1);
     }
 
-    match core_check_config(&config_path) {
-        Ok(_) => {
-            println!("Configuration check passed.");
+    if let Err(e) = core_check_config(&config_path, args.format) {
+        error!("{}", e);
+        process::exit(1);
+    }
+
+    if !args.strict {
+        println!("Configuration check passed.");
+        return;
+    }
+
+    let config = load_config(&config_path, args.format).unwrap_or_else(|e| {
+        error!("Failed to load config: {}", e);
+        process::exit(1);
+    });
+    let findings = lint::lint_config(&config);
+    let errors = findings
+        .iter()
+        .filter(|f| f.severity == Severity::Error)
+        .count();
+
+    if args.json {
+        match serde_json::to_string_pretty(&findings) {
+            Ok(json) => println!("{}", json),
+            Err(e) => {
+                error!("Failed to serialize findings: {}", e);
+                process::exit(1);
+            }
         }
-        Err(e) => {
-            error!("{}", e);
-            process::exit(1);
+    } else {
+        lint::print_findings(&findings);
+        if errors == 0 {
+            println!(
+                "Configuration check passed ({} warnings).",
+                findings.len() - errors
+            );
+        } else {
+            println!(
+                "Configuration check failed: {} errors, {} warnings.",
+                errors,
+                findings.len() - errors
+            );
         }
     }
+
+    if errors > 0 {
+        process::exit(1);
+    }
 }
 
 pub fn handle_service_command(args: ServiceArgs, user: bool) {
@@ -472,7 +720,7 @@ pub async fn handle_exec_command(args: ExecArgs) {
         process::exit(1);
     }
 
-    match core_check_config(&config_path) {
+    match core_check_config(&config_path, args.format) {
         Ok(_) => {
             info!("Configuration validated successfully.");
         }
@@ -483,7 +731,7 @@ pub async fn handle_exec_command(args: ExecArgs) {
         }
     }
 
-    let config = match load_config(&config_path) {
+    let config = match load_config(&config_path, args.format) {
         Ok(c) => c,
         Err(e) => {
             error!("Failed to load config: {}", e);
@@ -497,16 +745,16 @@ pub async fn handle_exec_command(args: ExecArgs) {
         Some(task) => {
             info!("Manually executing task: '{}'", task.name);
 
-            TaskScheduler::execute_command(
-                &task.name,
-                &task.command,
-                &task.args.as_deref().unwrap_or(&[]),
-                task.timeout,
-                task.webhook_url.as_deref(),
-                task.cwd.as_deref(),
-                task.env.as_ref(),
-            )
-            .await;
+            let (_stop_tx, stopping) = watch::channel(None);
+            let (_cancel_tx, cancel) = watch::channel(0);
+            let runner = Runner::new(
+                ChildRegistry::default(),
+                open_history_store(),
+                open_spool(),
+                stopping,
+            );
+            runner.run_task(task, &RunContext::manual(), cancel).await;
+            runner.wait_for_notifications().await;
 
             info!("Manual execution finished.");
         }
@@ -518,3 +766,145 @@ pub async fn handle_exec_command(args: ExecArgs) {
         }
     }
 }
+
+fn open_spool() -> Option<Spool> {
+    match utils::get_spool_dir() {
+        Ok(dir) => Some(Spool::new(dir)),
+        Err(e) => {
+            error!("Notification spool disabled: {}", e);
+            None
+        }
+    }
+}
+
+fn open_history_store() -> Option<HistoryStore> {
+    match utils::get_history_path() {
+        Ok(path) => {
+            debug!("Run history path: {}", path.display());
+            Some(HistoryStore::new(path))
+        }
+        Err(e) => {
+            error!("Run history disabled: {}", e);
+            None
+        }
+    }
+}
+
+pub fn handle_history_command(args: HistoryArgs) {
+    debug!("Entered handle_history_command with args: {:?}", args);
+    let store = match open_history_store() {
+        Some(s) => s,
+        None => process::exit(1),
+    };
+
+    let records = store.load().unwrap_or_else(|e| {
+        error!(
+            "Failed to read run history from {}: {}",
+            store.path().display(),
+            e
+        );
+        process::exit(1);
+    });
+
+    let filter = HistoryFilter {
+        task: args.task,
+        status: args.status,
+        since: args.since,
+        until: args.until,
+    };
+
+    let mut matching: Vec<_> = records.into_iter().filter(|r| filter.matches(r)).collect();
+    if args.limit > 0 && matching.len() > args.limit {
+        matching.drain(..matching.len() - args.limit);
+    }
+
+    if args.json {
+        match serde_json::to_string_pretty(&matching) {
+            Ok(json) => println!("{}", json),
+            Err(e) => {
+                error!("Failed to serialize run history: {}", e);
+                process::exit(1);
+            }
+        }
+    } else if matching.is_empty() {
+        println!("No matching runs in {}", store.path().display());
+    } else {
+        history::print_table(&matching);
+    }
+}
+
+fn resolve_socket_path(socket: Option<PathBuf>) -> PathBuf {
+    match socket {
+        Some(p) => p,
+        None => match utils::get_socket_path() {
+            Ok(p) => p,
+            Err(e) => {
+                error!("Error: Failed to determine control socket path.");
+                error!("Reason: {}", e);
+                process::exit(1);
+            }
+        },
+    }
+}
+
+async fn send_control_request(socket: Option<PathBuf>, request: ControlRequest) -> ControlResponse {
+    let socket_path = resolve_socket_path(socket);
+    debug!("Sending {:?} to {}", request, socket_path.display());
+
+    let response = control::send_request(&socket_path, &request)
+        .await
+        .unwrap_or_else(|e| {
+            error!("{}", e);
+            process::exit(1);
+        });
+
+    if !response.ok {
+        error!("{}", response.message);
+        process::exit(1);
+    }
+    response
+}
+
+pub async fn handle_control_command(socket: Option<PathBuf>, request: ControlRequest) {
+    let response = send_control_request(socket, request).await;
+    println!("{}", response.message);
+}
+
+pub async fn handle_status_command(args: StatusArgs) {
+    debug!("Entered handle_status_command with args: {:?}", args);
+    let response = send_control_request(args.socket, ControlRequest::Status).await;
+    let tasks = response.tasks.unwrap_or_default();
+
+    if args.json {
+        match serde_json::to_string_pretty(&tasks) {
+            Ok(json) => println!("{}", json),
+            Err(e) => {
+                error!("Failed to serialize status: {}", e);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
+    println!(
+        "{:<24}  {:<7}  {:>7}  {:>6}  {:<19}  LAST RESULT",
+        "TASK", "STATE", "RUNNING", "QUEUED", "NEXT RUN"
+    );
+    for task in tasks {
+        let state = if task.paused { "paused" } else { "active" };
+        let next_run = task
+            .next_run
+            .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_else(|| "-".to_string());
+        let last = match (task.last_started_at, task.last_outcome) {
+            (Some(at), Some(outcome)) => {
+                format!("{} ({})", outcome, at.format("%Y-%m-%d %H:%M:%S"))
+            }
+            _ => "-".to_string(),
+        };
+        println!(
+            "{:<24}  {:<7}  {:>7}  {:>6}  {:<19}  {}",
+            task.name, state, task.running, task.queued, next_run, last
+        );
+    }
+}
@@ -1,9 +1,18 @@
+use crate::dotenv;
+use crate::notify::{self, Notifier, Target};
+use crate::throttle;
+use chrono::{DateTime, Local};
+use chrono_tz::Tz;
+use clap::ValueEnum;
 use cron::Schedule;
-use serde::{Deserialize, Deserializer};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::collections::HashMap;
+use std::env;
 use std::error::Error;
-use std::path::Path;
+use std::fmt;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::time::Duration;
 
 fn deserialize_schedule<'de, D>(deserializer: D) -> Result<Schedule, D::Error>
 where
@@ -11,10 +20,153 @@ where
 {
     let s = String::deserialize(deserializer)?;
 
-    Schedule::from_str(&s).map_err(serde::de::Error::custom)
+    Schedule::from_str(&s).map_err(|e| {
+        // The cron crate prints its own snippet above the reason; keep only
+        // the reason, since the config snippet is rendered separately.
+        let message = e.to_string();
+        let reason = message.lines().last().unwrap_or_default();
+        serde::de::Error::custom(format!("invalid cron expression '{}': {}", s, reason))
+    })
 }
 
-#[derive(Debug, Deserialize, Clone)]
+/// Accepts either a single path or a list of paths.
+fn deserialize_paths<'de, D>(deserializer: D) -> Result<Vec<PathBuf>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(PathBuf),
+        Many(Vec<PathBuf>),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(path) => vec![path],
+        OneOrMany::Many(paths) => paths,
+    })
+}
+
+/// What to do when a task fires while its previous run is still in progress.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum OverlapPolicy {
+    /// Drop the new fire and log a warning.
+    #[default]
+    Skip,
+    /// Run the new fire once the previous run has finished. Scheduled fires
+    /// are merged into one pending run, and at most a few runs wait at once.
+    Queue,
+    /// Cancel the previous run, the same way `chronsync cancel` does, and
+    /// start the new one.
+    Replace,
+    /// Start the new run alongside the previous one.
+    Allow,
+}
+
+/// How the delay between retries grows.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum RetryBackoff {
+    /// Wait `retry_delay` before every retry.
+    #[default]
+    Fixed,
+    /// Double the delay after every failed attempt, up to `retry_max_delay`.
+    Exponential,
+}
+
+/// Run results that can be reported to a task's `webhook_url`.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NotifyEvent {
+    /// The command exited with a non-zero code or was killed by a signal.
+    Failure,
+    /// The run hit its timeout.
+    Timeout,
+    /// The command could not be started, e.g. because it does not exist.
+    SpawnError,
+    /// The first successful run after a failed one.
+    Recovered,
+    /// Any successful run.
+    Success,
+}
+
+impl fmt::Display for NotifyEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            NotifyEvent::Failure => "failure",
+            NotifyEvent::Timeout => "timeout",
+            NotifyEvent::SpawnError => "spawn_error",
+            NotifyEvent::Recovered => "recovered",
+            NotifyEvent::Success => "success",
+        })
+    }
+}
+
+/// Events notified when a task does not set `notify_on`.
+pub const DEFAULT_NOTIFY_ON: &[NotifyEvent] = &[
+    NotifyEvent::Failure,
+    NotifyEvent::Timeout,
+    NotifyEvent::SpawnError,
+];
+
+/// When a task's `log_file` is rotated and how many old files are kept.
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+pub struct LogRotation {
+    /// Rotate before a write would make the file larger than this.
+    pub max_bytes: Option<u64>,
+    /// Rotate on the first write of a new day.
+    #[serde(default)]
+    pub daily: bool,
+    /// Number of rotated files (`<file>.1`, `<file>.2`, ...) to keep.
+    #[serde(default = "default_log_keep")]
+    pub keep: usize,
+}
+
+fn default_log_keep() -> usize {
+    7
+}
+
+impl Default for LogRotation {
+    fn default() -> Self {
+        LogRotation {
+            max_bytes: None,
+            daily: false,
+            keep: default_log_keep(),
+        }
+    }
+}
+
+/// Limits on how often a task's notifications are sent, so that a task
+/// that keeps failing does not flood its notify targets.
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+pub struct NotifyThrottle {
+    /// Only notify when a run ends differently from the previous one.
+    /// Repeats are counted instead.
+    #[serde(default = "default_on_change")]
+    pub on_change: bool,
+    /// Most notifications sent per `window`.
+    pub max_alerts: Option<u32>,
+    /// Seconds `max_alerts` applies to.
+    #[serde(default = "default_throttle_window")]
+    pub window: u64,
+    /// Seconds between "still failing" digests while repeated failures are
+    /// suppressed. No digests are sent when unset.
+    pub digest_interval: Option<u64>,
+}
+
+fn default_on_change() -> bool {
+    true
+}
+
+fn default_throttle_window() -> u64 {
+    3600
+}
+
+const DEFAULT_RETRY_DELAY: u64 = 10;
+const DEFAULT_RETRY_MAX_DELAY: u64 = 3600;
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
 pub struct Task {
     pub name: String,
 
@@ -27,27 +179,896 @@ pub struct Task {
     #[serde(default)]
     pub timeout: Option<u64>,
 
+    /// Seconds to wait after SIGTERM on timeout before sending SIGKILL.
+    pub kill_grace: Option<u64>,
+
+    /// Bytes of stdout and of stderr kept for history and alerts. Only the
+    /// tail is kept; the full output is streamed to the log.
+    pub max_output_bytes: Option<usize>,
+
+    /// File every run's output is appended to, after a header line. Relative
+    /// paths are resolved against the directory of the configuration file.
+    pub log_file: Option<PathBuf>,
+
+    /// Rotation of `log_file`. Falls back to the top-level `log_rotate`.
+    pub log_rotate: Option<LogRotation>,
+
     pub webhook_url: Option<String>,
 
+    /// Names of entries in the top-level `notifiers` to send events to, in
+    /// addition to `webhook_url`.
+    pub notify: Option<Vec<String>>,
+
+    /// The notifiers named in `notify`, filled in when the configuration is
+    /// parsed.
+    #[serde(skip)]
+    pub notifiers: Vec<(String, Notifier)>,
+
+    /// Message template used for every notify target of this task instead
+    /// of the notifier's own `template`.
+    pub notify_template: Option<String>,
+
+    /// Events sent to `webhook_url` and `notify`. Defaults to
+    /// [`DEFAULT_NOTIFY_ON`].
+    pub notify_on: Option<Vec<NotifyEvent>>,
+
+    /// Deduplication and rate limit of this task's notifications.
+    pub notify_throttle: Option<NotifyThrottle>,
+
     pub cwd: Option<String>,
 
     pub env: Option<HashMap<String, String>>,
+
+    /// Dotenv files loaded beneath `env`. Relative paths are resolved
+    /// against the directory of the configuration file.
+    #[serde(default, deserialize_with = "deserialize_paths")]
+    pub env_file: Vec<PathBuf>,
+
+    /// Start from an empty environment instead of the daemon's, keeping only
+    /// the variables named in `inherit_env`.
+    #[serde(default)]
+    pub env_clear: bool,
+
+    /// Variables passed through from the base environment when `env_clear`
+    /// is set.
+    #[serde(default)]
+    pub inherit_env: Vec<String>,
+
+    /// Use the environment of the user's login shell, captured once, as the
+    /// base environment instead of the daemon's.
+    #[serde(default)]
+    pub login_env: bool,
+
+    #[serde(default)]
+    pub overlap: OverlapPolicy,
+
+    /// Number of times a failed run is retried before it counts as a failure.
+    pub retries: Option<u32>,
+
+    /// Seconds to wait before the first retry.
+    pub retry_delay: Option<u64>,
+
+    #[serde(default)]
+    pub retry_backoff: RetryBackoff,
+
+    /// Upper bound in seconds for exponential backoff.
+    pub retry_max_delay: Option<u64>,
+
+    /// IANA timezone the cron schedule is evaluated in. Falls back to the
+    /// top-level `timezone`, then to the system's local time.
+    pub timezone: Option<Tz>,
+}
+
+impl Task {
+    /// Expands `${VAR}` references in the fields that accept them. On
+    /// failure, returns the name of the offending field with the error.
+    fn interpolate(
+        &mut self,
+        lookup: &dyn Fn(&str) -> Option<String>,
+    ) -> Result<(), (String, InterpolationError)> {
+        let expand = |field: String, value: &mut String| {
+            *value = interpolate(value, lookup).map_err(|e| (field, e))?;
+            Ok(())
+        };
+
+        expand("command".to_string(), &mut self.command)?;
+        for (i, arg) in self.args.iter_mut().flatten().enumerate() {
+            expand(format!("args[{}]", i), arg)?;
+        }
+        if let Some(cwd) = &mut self.cwd {
+            expand("cwd".to_string(), cwd)?;
+        }
+        if let Some(url) = &mut self.webhook_url {
+            expand("webhook_url".to_string(), url)?;
+        }
+        for (key, value) in self.env.iter_mut().flatten() {
+            expand(format!("env.{}", key), value)?;
+        }
+        Ok(())
+    }
+
+    /// Whether the task has a webhook or notifier to report events to.
+    pub fn has_notify_targets(&self) -> bool {
+        self.webhook_url.is_some() || !self.notifiers.is_empty()
+    }
+
+    /// The events this task reports to its notify targets.
+    pub fn notify_events(&self) -> &[NotifyEvent] {
+        self.notify_on.as_deref().unwrap_or(DEFAULT_NOTIFY_ON)
+    }
+
+    /// Returns the delay before the retry that follows failed attempt
+    /// number `attempt` (starting at 1).
+    pub fn retry_delay(&self, attempt: u32) -> Duration {
+        let base = self.retry_delay.unwrap_or(DEFAULT_RETRY_DELAY);
+        let secs = match self.retry_backoff {
+            RetryBackoff::Fixed => base,
+            RetryBackoff::Exponential => {
+                let max = self.retry_max_delay.unwrap_or(DEFAULT_RETRY_MAX_DELAY);
+                let factor = 2u64.saturating_pow(attempt.saturating_sub(1));
+                base.saturating_mul(factor).min(max)
+            }
+        };
+        Duration::from_secs(secs)
+    }
+
+    /// Returns the first fire time strictly after `after`, evaluating the
+    /// schedule in the task's timezone.
+    pub fn next_fire_after(&self, after: &DateTime<Local>) -> Option<DateTime<Local>> {
+        match self.timezone {
+            Some(tz) => self
+                .cron_schedule
+                .after(&after.with_timezone(&tz))
+                .next()
+                .map(|t| t.with_timezone(&Local)),
+            None => self.cron_schedule.after(after).next(),
+        }
+    }
+}
+
+/// Expands `${VAR}` references in the fields of a notifier. On failure,
+/// returns the name of the offending field with the error.
+fn interpolate_notifier(
+    notifier: &mut Notifier,
+    lookup: &dyn Fn(&str) -> Option<String>,
+) -> Result<(), (String, InterpolationError)> {
+    let expand = |field: String, value: &mut String| {
+        *value = interpolate(value, lookup).map_err(|e| (field, e))?;
+        Ok(())
+    };
+
+    if let Some(secret) = &mut notifier.secret {
+        expand("secret".to_string(), secret)?;
+    }
+    match &mut notifier.target {
+        Target::Slack { url } | Target::Discord { url } => expand("url".to_string(), url),
+        Target::Webhook { url, headers } => {
+            expand("url".to_string(), url)?;
+            for (name, value) in headers.iter_mut() {
+                expand(format!("headers.{}", name), value)?;
+            }
+            Ok(())
+        }
+        Target::Command { command, args } => {
+            expand("command".to_string(), command)?;
+            for (i, arg) in args.iter_mut().enumerate() {
+                expand(format!("args[{}]", i), arg)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+#[derive(Debug)]
+struct InterpolationError {
+    /// The variable that could not be resolved, if any.
+    name: Option<String>,
+    message: String,
+}
+
+/// Expands `${VAR}` and `${VAR:-default}` in `value`. `$${` is an escaped,
+/// literal `${`. Referencing an undefined variable without a default fails.
+fn interpolate(
+    value: &str,
+    lookup: &dyn Fn(&str) -> Option<String>,
+) -> Result<String, InterpolationError> {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(start) = rest.find('$') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+
+        if let Some(escaped) = after.strip_prefix("${") {
+            out.push_str("${");
+            rest = escaped;
+            continue;
+        }
+        let Some(body) = after.strip_prefix('{') else {
+            out.push('$');
+            rest = after;
+            continue;
+        };
+        let Some(end) = body.find('}') else {
+            return Err(InterpolationError {
+                name: None,
+                message: format!("unterminated '${{' in '{}'", value),
+            });
+        };
+
+        let expr = &body[..end];
+        let (name, default) = match expr.split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (expr, None),
+        };
+        match lookup(name).or_else(|| default.map(String::from)) {
+            Some(resolved) => out.push_str(&resolved),
+            None => {
+                return Err(InterpolationError {
+                    name: Some(name.to_string()),
+                    message: format!("undefined variable '{}'", name),
+                })
+            }
+        }
+        rest = &body[end + 1..];
+    }
+
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Values inherited by every task that does not set them itself.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct TaskDefaults {
+    pub timeout: Option<u64>,
+    pub max_output_bytes: Option<usize>,
+    pub webhook_url: Option<String>,
+    pub notify: Option<Vec<String>>,
+    pub notify_on: Option<Vec<NotifyEvent>>,
+    pub notify_throttle: Option<NotifyThrottle>,
+    pub cwd: Option<String>,
+    /// Merged with the task's own `env`; the task wins on conflicting keys.
+    pub env: Option<HashMap<String, String>>,
+}
+
+impl TaskDefaults {
+    fn apply(&self, task: &mut Task) {
+        task.timeout = task.timeout.or(self.timeout);
+        task.max_output_bytes = task.max_output_bytes.or(self.max_output_bytes);
+        if task.webhook_url.is_none() {
+            task.webhook_url = self.webhook_url.clone();
+        }
+        if task.notify.is_none() {
+            task.notify = self.notify.clone();
+        }
+        if task.notify_on.is_none() {
+            task.notify_on = self.notify_on.clone();
+        }
+        if task.notify_throttle.is_none() {
+            task.notify_throttle = self.notify_throttle.clone();
+        }
+        if task.cwd.is_none() {
+            task.cwd = self.cwd.clone();
+        }
+        if let Some(defaults) = &self.env {
+            let mut env = defaults.clone();
+            env.extend(task.env.take().unwrap_or_default());
+            task.env = Some(env);
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct Config {
+    /// Default timezone for tasks that do not set their own.
+    pub timezone: Option<Tz>,
+
+    #[serde(default)]
+    pub defaults: TaskDefaults,
+
+    /// Variables available to `${VAR}` references in task fields. They take
+    /// precedence over the daemon's environment.
+    #[serde(default)]
+    pub vars: HashMap<String, String>,
+
+    /// Dotenv files loaded for every task, beneath the task's own
+    /// `env_file` and `env`.
+    #[serde(default, deserialize_with = "deserialize_paths")]
+    pub env_file: Vec<PathBuf>,
+
+    /// Directory where tasks without a `log_file` write `<name>.log`.
+    pub task_log_dir: Option<PathBuf>,
+
+    /// Default rotation for task log files.
+    pub log_rotate: Option<LogRotation>,
+
+    /// Notification targets that tasks refer to by name in `notify`.
+    #[serde(default)]
+    pub notifiers: HashMap<String, Notifier>,
+
     pub tasks: Vec<Task>,
 }
 
-pub fn load_config(path: &Path) -> Result<Config, Box<dyn Error>> {
+impl Config {
+    /// Every env file referenced by the configuration, so the watcher can
+    /// reload when one of them changes.
+    pub fn env_files(&self) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = self.env_file.clone();
+        for task in &self.tasks {
+            files.extend(task.env_file.iter().cloned());
+        }
+        files.sort();
+        files.dedup();
+        files
+    }
+
+    /// Resolves env file paths against `base` and merges their variables
+    /// into each task's `env`. Later files override earlier ones, task files
+    /// override global ones, and the inline `env` overrides them all.
+    fn load_env_files(&mut self, base: &Path) -> Result<(), Box<dyn Error>> {
+        for file in &mut self.env_file {
+            *file = base.join(&*file);
+        }
+        let mut global = HashMap::new();
+        for file in &self.env_file {
+            global.extend(dotenv::load(file)?);
+        }
+
+        for task in &mut self.tasks {
+            for file in &mut task.env_file {
+                *file = base.join(&*file);
+            }
+            if global.is_empty() && task.env_file.is_empty() {
+                continue;
+            }
+
+            let mut env = global.clone();
+            for file in &task.env_file {
+                env.extend(dotenv::load(file)?);
+            }
+            env.extend(task.env.take().unwrap_or_default());
+            task.env = Some(env);
+        }
+        Ok(())
+    }
+}
+
+/// The file formats a configuration can be written in. All of them share
+/// the same `Config`/`Task` schema.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ConfigFormat {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl ConfigFormat {
+    /// Guesses the format from the file extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "json" => Some(ConfigFormat::Json),
+            "toml" => Some(ConfigFormat::Toml),
+            "yaml" | "yml" => Some(ConfigFormat::Yaml),
+            _ => None,
+        }
+    }
+
+    /// Uses the explicit format if given, otherwise the file extension, and
+    /// falls back to JSON.
+    pub fn resolve(path: &Path, explicit: Option<Self>) -> Self {
+        explicit
+            .or_else(|| ConfigFormat::from_path(path))
+            .unwrap_or(ConfigFormat::Json)
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ConfigFormat::Json => "json",
+            ConfigFormat::Toml => "toml",
+            ConfigFormat::Yaml => "yaml",
+        }
+    }
+
+    /// A sample configuration written by `chronsync init`.
+    pub fn template(&self) -> &'static str {
+        match self {
+            ConfigFormat::Json => JSON_TEMPLATE,
+            ConfigFormat::Toml => TOML_TEMPLATE,
+            ConfigFormat::Yaml => YAML_TEMPLATE,
+        }
+    }
+}
+
+impl fmt::Display for ConfigFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ConfigFormat::Json => "JSON",
+            ConfigFormat::Toml => "TOML",
+            ConfigFormat::Yaml => "YAML",
+        };
+        f.write_str(name)
+    }
+}
+
+const JSON_TEMPLATE: &str = r#"{
+  "tasks": [
+    {
+      "name": "sample_ping",
+      "cron_schedule": "*/10 * * * * *",
+      "command": "/bin/sh",
+      "args": [
+        "-c", "/bin/echo \"[Sample] Check at $(date)\""
+      ]
+    },
+    {
+      "name": "sample_cleanup",
+      "cron_schedule": "0 0 0 * * *",
+      "command": "/usr/bin/find",
+      "args": ["/tmp", "-type", "f", "-atime", "+7", "-delete"]
+    }
+  ]
+}
+"#;
+
+const TOML_TEMPLATE: &str = r#"# chronsync configuration
+# cron_schedule: sec min hour day-of-month month day-of-week [year]
+
+[[tasks]]
+name = "sample_ping"
+cron_schedule = "*/10 * * * * *"
+command = "/bin/sh"
+args = ["-c", '/bin/echo "[Sample] Check at $(date)"']
+
+[[tasks]]
+name = "sample_cleanup"
+cron_schedule = "0 0 0 * * *"
+command = "/usr/bin/find"
+args = ["/tmp", "-type", "f", "-atime", "+7", "-delete"]
+"#;
+
+const YAML_TEMPLATE: &str = r#"# chronsync configuration
+# cron_schedule: sec min hour day-of-month month day-of-week [year]
+
+tasks:
+  - name: sample_ping
+    cron_schedule: "*/10 * * * * *"
+    command: /bin/sh
+    args:
+      - -c
+      - /bin/echo "[Sample] Check at $(date)"
+
+  - name: sample_cleanup
+    cron_schedule: "0 0 0 * * *"
+    command: /usr/bin/find
+    args: ["/tmp", "-type", "f", "-atime", "+7", "-delete"]
+"#;
+
+/// A configuration that could not be parsed, with enough context to point
+/// at the offending value.
+#[derive(Debug)]
+pub struct ConfigError {
+    pub message: String,
+    /// The file the configuration was read from.
+    pub file: Option<PathBuf>,
+    /// Path of the failing value, e.g. `tasks[2].cron_schedule`.
+    pub field: Option<String>,
+    /// Name of the task the failing value belongs to.
+    pub task: Option<String>,
+    /// 1-based line and column of the failing value.
+    pub position: Option<(usize, usize)>,
+    source_line: Option<String>,
+}
+
+impl ConfigError {
+    fn new(
+        content: &str,
+        format: ConfigFormat,
+        path: Option<&serde_path_to_error::Path>,
+        message: String,
+        position: Option<(usize, usize)>,
+    ) -> Box<Self> {
+        let mut segments = path.into_iter().flat_map(|p| p.iter());
+        let task_index = match (segments.next(), segments.next()) {
+            (
+                Some(serde_path_to_error::Segment::Map { key }),
+                Some(serde_path_to_error::Segment::Seq { index }),
+            ) if key == "tasks" => Some(*index),
+            _ => None,
+        };
+        let field = path
+            .map(|p| p.to_string())
+            .map(|p| p.trim_end_matches(".?").to_string())
+            .filter(|p| p != "." && p != "?");
+        let message = match &field {
+            Some(field) => message
+                .strip_prefix(&format!("{}: ", field))
+                .map(String::from)
+                .unwrap_or(message),
+            None => message,
+        };
+
+        Box::new(ConfigError {
+            message,
+            file: None,
+            field,
+            task: task_index.and_then(|i| task_name_at(content, format, i)),
+            position,
+            source_line: source_line(content, position),
+        })
+    }
+
+    fn with_file(mut self: Box<Self>, file: &Path) -> Box<Self> {
+        self.file = Some(file.to_path_buf());
+        self
+    }
+}
+
+/// Renders the error the way rustc does: the message, the location, the
+/// source line with a caret under the failing column, and the task/field.
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+
+        let file = self
+            .file
+            .as_ref()
+            .map(|p| p.display().to_string())
+            .unwrap_or_else(|| "<config>".to_string());
+        match self.position {
+            Some((line, column)) => {
+                let gutter = " ".repeat(line.to_string().len());
+                write!(f, "\n{}--> {}:{}:{}", gutter, file, line, column)?;
+                if let Some(source) = &self.source_line {
+                    let caret = column.saturating_sub(1).min(source.chars().count());
+                    let indent: String = source
+                        .chars()
+                        .take(caret)
+                        .map(|c| if c == '\t' { '\t' } else { ' ' })
+                        .collect();
+                    write!(f, "\n{} |", gutter)?;
+                    write!(f, "\n{} | {}", line, source)?;
+                    write!(f, "\n{} | {}^", gutter, indent)?;
+                }
+            }
+            None if self.file.is_some() => write!(f, "\n --> {}", file)?,
+            None => {}
+        }
+
+        match (&self.task, &self.field) {
+            (Some(task), Some(field)) => write!(f, "\n = in task '{}', field `{}`", task, field),
+            (None, Some(field)) => write!(f, "\n = in field `{}`", field),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+/// Looks up the `name` of the task at `index` without the strict schema, so
+/// it can be reported even when that task is the one that failed to parse.
+fn task_name_at(content: &str, format: ConfigFormat, index: usize) -> Option<String> {
+    let value: serde_json::Value = match format {
+        ConfigFormat::Json => serde_json::from_str(content).ok()?,
+        ConfigFormat::Toml => toml::from_str(content).ok()?,
+        ConfigFormat::Yaml => serde_yaml::from_str(content).ok()?,
+    };
+    value
+        .get("tasks")?
+        .get(index)?
+        .get("name")?
+        .as_str()
+        .map(String::from)
+}
+
+fn source_line(content: &str, position: Option<(usize, usize)>) -> Option<String> {
+    let (line, _) = position?;
+    content.lines().nth(line.checked_sub(1)?).map(String::from)
+}
+
+/// Converts a byte offset into a 1-based line and column.
+fn line_column(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset.min(content.len())];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+    (line, before[line_start..].chars().count() + 1)
+}
+
+/// serde_json reports errors raised while deserializing a string value at
+/// its closing quote. Moves the position back to the opening quote so the
+/// caret points at the start of the value.
+fn json_value_start(content: &str, line: usize, column: usize) -> (usize, usize) {
+    let Some(source) = content.lines().nth(line - 1) else {
+        return (line, column);
+    };
+    let chars: Vec<char> = source.chars().collect();
+    let end = column.saturating_sub(1);
+    if chars.get(end) != Some(&'"') {
+        return (line, column);
+    }
+    match chars[..end].iter().rposition(|&c| c == '"') {
+        Some(start) => (line, start + 1),
+        None => (line, column),
+    }
+}
+
+/// Strips the ` at line N column M` suffix that serde_json and serde_yaml
+/// append, since the position is rendered separately.
+fn strip_position(message: String) -> String {
+    match message.rfind(" at line ") {
+        Some(i) => message[..i].to_string(),
+        None => message,
+    }
+}
+
+pub fn parse_config(content: &str, format: ConfigFormat) -> Result<Config, Box<ConfigError>> {
+    parse_config_with_env(content, format, &|name| env::var(name).ok())
+}
+
+/// Like [`parse_config`], but `${VAR}` references that are not in `vars`
+/// are looked up with `env` instead of in the daemon's environment.
+fn parse_config_with_env(
+    content: &str,
+    format: ConfigFormat,
+    env: &dyn Fn(&str) -> Option<String>,
+) -> Result<Config, Box<ConfigError>> {
+    let mut config: Config = match format {
+        ConfigFormat::Json => {
+            let de = &mut serde_json::Deserializer::from_str(content);
+            serde_path_to_error::deserialize(de).map_err(|e| {
+                let path = e.path().clone();
+                let inner = e.into_inner();
+                let position = (inner.line() > 0)
+                    .then(|| json_value_start(content, inner.line(), inner.column()));
+                let message = strip_position(inner.to_string());
+                ConfigError::new(content, format, Some(&path), message, position)
+            })?
+        }
+        ConfigFormat::Toml => {
+            let de = toml::Deserializer::parse(content).map_err(|e| {
+                let position = e.span().map(|span| line_column(content, span.start));
+                ConfigError::new(content, format, None, e.message().to_string(), position)
+            })?;
+            serde_path_to_error::deserialize(de).map_err(|e| {
+                let path = e.path().clone();
+                let inner = e.into_inner();
+                let position = inner.span().map(|span| line_column(content, span.start));
+                ConfigError::new(
+                    content,
+                    format,
+                    Some(&path),
+                    inner.message().to_string(),
+                    position,
+                )
+            })?
+        }
+        ConfigFormat::Yaml => {
+            let de = serde_yaml::Deserializer::from_str(content);
+            serde_path_to_error::deserialize(de).map_err(|e| {
+                let path = e.path().clone();
+                let inner = e.into_inner();
+                let position = inner.location().map(|l| (l.line(), l.column()));
+                let message = strip_position(inner.to_string());
+                ConfigError::new(content, format, Some(&path), message, position)
+            })?
+        }
+    };
+
+    let vars = &config.vars;
+    let lookup = |name: &str| vars.get(name).cloned().or_else(|| env(name));
+    let variable_position = |e: &InterpolationError| {
+        e.name
+            .as_ref()
+            .and_then(|name| content.find(&format!("${{{}", name)))
+            .map(|offset| line_column(content, offset))
+    };
+
+    let check_template = |template: Option<&str>, field: String, task: Option<&str>| {
+        let Some(unknown) = template.and_then(notify::unknown_placeholder) else {
+            return Ok(());
+        };
+        let position = content
+            .find(&format!("{{{{{}", unknown))
+            .or_else(|| content.find(unknown))
+            .map(|offset| line_column(content, offset));
+        Err(Box::new(ConfigError {
+            message: format!(
+                "unknown placeholder '{{{{{}}}}}' (expected one of: {})",
+                unknown,
+                notify::PLACEHOLDERS.join(", ")
+            ),
+            file: None,
+            field: Some(field),
+            task: task.map(str::to_string),
+            position,
+            source_line: source_line(content, position),
+        }))
+    };
+
+    for (name, notifier) in config.notifiers.iter_mut() {
+        check_template(
+            notifier.template.as_deref(),
+            format!("notifiers.{}.template", name),
+            None,
+        )?;
+        interpolate_notifier(notifier, &lookup).map_err(|(field, e)| {
+            let position = variable_position(&e);
+            Box::new(ConfigError {
+                message: e.message,
+                file: None,
+                field: Some(format!("notifiers.{}.{}", name, field)),
+                task: None,
+                position,
+                source_line: source_line(content, position),
+            })
+        })?;
+    }
+
+    for (index, task) in config.tasks.iter_mut().enumerate() {
+        task.timezone = task.timezone.or(config.timezone);
+        if task.log_file.is_none() {
+            task.log_file = config
+                .task_log_dir
+                .as_ref()
+                .map(|dir| dir.join(format!("{}.log", task.name)));
+        }
+        if task.log_file.is_some() && task.log_rotate.is_none() {
+            task.log_rotate = Some(config.log_rotate.clone().unwrap_or_default());
+        }
+        config.defaults.apply(task);
+        task.interpolate(&lookup).map_err(|(field, e)| {
+            let position = variable_position(&e);
+            Box::new(ConfigError {
+                message: e.message,
+                file: None,
+                field: Some(format!("tasks[{}].{}", index, field)),
+                task: Some(task.name.clone()),
+                position,
+                source_line: source_line(content, position),
+            })
+        })?;
+
+        check_template(
+            task.notify_template.as_deref(),
+            format!("tasks[{}].notify_template", index),
+            Some(&task.name),
+        )?;
+
+        for (i, name) in task.notify.iter().flatten().enumerate() {
+            let Some(notifier) = config.notifiers.get(name) else {
+                let position = content
+                    .find(&format!("\"{}\"", name))
+                    .map(|offset| line_column(content, offset));
+                return Err(Box::new(ConfigError {
+                    message: format!("unknown notifier '{}'", name),
+                    file: None,
+                    field: Some(format!("tasks[{}].notify[{}]", index, i)),
+                    task: Some(task.name.clone()),
+                    position,
+                    source_line: source_line(content, position),
+                }));
+            };
+            task.notifiers.push((name.clone(), notifier.clone()));
+        }
+
+        if let Some(limits) = &task.notify_throttle {
+            let intervals = [
+                ("window", Some(limits.window)),
+                ("digest_interval", limits.digest_interval),
+            ];
+            for (field, secs) in intervals {
+                let Some(secs) = secs.filter(|&secs| throttle::seconds(secs).is_none()) else {
+                    continue;
+                };
+                let position = content
+                    .find(&secs.to_string())
+                    .map(|offset| line_column(content, offset));
+                return Err(Box::new(ConfigError {
+                    message: format!("{} seconds is out of range", secs),
+                    file: None,
+                    field: Some(format!("tasks[{}].notify_throttle.{}", index, field)),
+                    task: Some(task.name.clone()),
+                    position,
+                    source_line: source_line(content, position),
+                }));
+            }
+        }
+    }
+
+    Ok(config)
+}
+
+/// Loads the configuration at `path`. The format is taken from `format` if
+/// given, otherwise from the file extension.
+///
+/// If `path` is a directory, every configuration file below it is loaded
+/// and merged (see [`load_config_dir`]).
+pub fn load_config(path: &Path, format: Option<ConfigFormat>) -> Result<Config, Box<dyn Error>> {
     use std::fs;
 
-    let content = fs::read_to_string(path)?;
-    let config: Config = serde_json::from_str(&content)?;
+    if path.is_dir() {
+        return load_config_dir(path);
+    }
+
+    let content = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut config = parse_config(&content, ConfigFormat::resolve(path, format))
+        .map_err(|e| -> Box<dyn Error> { e.with_file(path) })?;
 
+    let base = path.parent().unwrap_or(Path::new("."));
+    config.load_env_files(base)?;
+    for task in &mut config.tasks {
+        if let Some(file) = &mut task.log_file {
+            *file = base.join(&*file);
+        }
+    }
     Ok(config)
 }
 
+/// Loads every `.json`, `.toml`, `.yaml` and `.yml` file below `dir` and
+/// merges their tasks into one `Config`. Each file is parsed on its own, so
+/// its top-level `timezone` and `defaults` only apply to its own tasks.
+/// Hidden files and files with other extensions are ignored.
+///
+/// Fails if a task name is defined in more than one file.
+pub fn load_config_dir(dir: &Path) -> Result<Config, Box<dyn Error>> {
+    let mut files = Vec::new();
+    collect_config_files(dir, &mut files)?;
+    files.sort();
+
+    let mut tasks: Vec<Task> = Vec::new();
+    let mut env_file: Vec<PathBuf> = Vec::new();
+    let mut sources: HashMap<String, PathBuf> = HashMap::new();
+
+    for file in files {
+        let config = load_config(&file, None)?;
+        env_file.extend(config.env_file);
+        for task in config.tasks {
+            if let Some(first) = sources.get(&task.name) {
+                return Err(format!(
+                    "Duplicate task name '{}' in {} (already defined in {})",
+                    task.name,
+                    file.display(),
+                    first.display()
+                )
+                .into());
+            }
+            sources.insert(task.name.clone(), file.clone());
+            tasks.push(task);
+        }
+    }
+
+    Ok(Config {
+        timezone: None,
+        defaults: TaskDefaults::default(),
+        vars: HashMap::new(),
+        env_file,
+        task_log_dir: None,
+        log_rotate: None,
+        notifiers: HashMap::new(),
+        tasks,
+    })
+}
+
+fn collect_config_files(dir: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let hidden = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| n.starts_with('.'));
+        if hidden {
+            continue;
+        }
+
+        if path.is_dir() {
+            collect_config_files(&path, files)?;
+        } else if ConfigFormat::from_path(&path).is_some() {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -72,6 +1093,428 @@ mod tests {
         assert_eq!(config.tasks.len(), 1);
         assert_eq!(config.tasks[0].name, "test_task");
         assert_eq!(config.tasks[0].timeout, Some(10));
+        assert_eq!(config.tasks[0].overlap, OverlapPolicy::Skip);
+    }
+
+    #[test]
+    fn test_overlap_policy_deserialization() {
+        let json_data = r#"
+        {
+            "tasks": [
+                {
+                    "name": "queued_task",
+                    "cron_schedule": "* * * * * *",
+                    "command": "echo",
+                    "overlap": "queue"
+                },
+                {
+                    "name": "bad_overlap",
+                    "cron_schedule": "* * * * * *",
+                    "command": "echo",
+                    "overlap": "sometimes"
+                }
+            ]
+        }"#;
+
+        let result: Result<Config, _> = serde_json::from_str(json_data);
+        assert!(result.is_err(), "Should fail on unknown overlap policy");
+
+        let json_data = json_data.replace("sometimes", "replace");
+        let config: Config = serde_json::from_str(&json_data).expect("Should deserialize");
+        assert_eq!(config.tasks[0].overlap, OverlapPolicy::Queue);
+        assert_eq!(config.tasks[1].overlap, OverlapPolicy::Replace);
+    }
+
+    #[test]
+    fn test_retry_delay_backoff() {
+        let json_data = r#"
+        {
+            "tasks": [
+                {
+                    "name": "fixed",
+                    "cron_schedule": "* * * * * *",
+                    "command": "echo",
+                    "retries": 3,
+                    "retry_delay": 5
+                },
+                {
+                    "name": "exponential",
+                    "cron_schedule": "* * * * * *",
+                    "command": "echo",
+                    "retries": 5,
+                    "retry_delay": 5,
+                    "retry_backoff": "exponential",
+                    "retry_max_delay": 30
+                }
+            ]
+        }"#;
+
+        let config: Config = serde_json::from_str(json_data).expect("Should deserialize");
+        let fixed = &config.tasks[0];
+        assert_eq!(fixed.retry_delay(1), Duration::from_secs(5));
+        assert_eq!(fixed.retry_delay(3), Duration::from_secs(5));
+
+        let exponential = &config.tasks[1];
+        assert_eq!(exponential.retry_delay(1), Duration::from_secs(5));
+        assert_eq!(exponential.retry_delay(2), Duration::from_secs(10));
+        assert_eq!(exponential.retry_delay(3), Duration::from_secs(20));
+        assert_eq!(exponential.retry_delay(4), Duration::from_secs(30));
+        assert_eq!(exponential.retry_delay(40), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_templates_parse_in_every_format() {
+        for format in [ConfigFormat::Json, ConfigFormat::Toml, ConfigFormat::Yaml] {
+            let config = parse_config(format.template(), format)
+                .unwrap_or_else(|e| panic!("{} template should parse: {}", format, e));
+            assert_eq!(config.tasks.len(), 2);
+            assert_eq!(config.tasks[0].name, "sample_ping");
+            assert_eq!(
+                config.tasks[0].args.as_deref().unwrap()[1],
+                r#"/bin/echo "[Sample] Check at $(date)""#
+            );
+        }
+    }
+
+    #[test]
+    fn test_format_from_extension() {
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("a/config.yml")),
+            Some(ConfigFormat::Yaml)
+        );
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("config.TOML")),
+            Some(ConfigFormat::Toml)
+        );
+        assert_eq!(ConfigFormat::from_path(Path::new("config")), None);
+        assert_eq!(
+            ConfigFormat::resolve(Path::new("config.yaml"), Some(ConfigFormat::Json)),
+            ConfigFormat::Json
+        );
+    }
+
+    #[test]
+    fn test_task_timezone_and_default() {
+        let json_data = r#"
+        {
+            "timezone": "UTC",
+            "tasks": [
+                {
+                    "name": "tokyo",
+                    "cron_schedule": "0 0 9 * * *",
+                    "command": "echo",
+                    "timezone": "Asia/Tokyo"
+                },
+                {
+                    "name": "default",
+                    "cron_schedule": "0 0 9 * * *",
+                    "command": "echo"
+                }
+            ]
+        }"#;
+
+        let config = parse_config(json_data, ConfigFormat::Json).expect("Should deserialize");
+        assert_eq!(config.tasks[0].timezone, Some(chrono_tz::Asia::Tokyo));
+        assert_eq!(config.tasks[1].timezone, Some(chrono_tz::UTC));
+
+        // 09:00 in Tokyo is 00:00 UTC.
+        let after = DateTime::parse_from_rfc3339("2025-01-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Local);
+        let next = config.tasks[0].next_fire_after(&after).unwrap();
+        assert_eq!(
+            next,
+            DateTime::parse_from_rfc3339("2025-01-02T00:00:00Z").unwrap()
+        );
+        let next = config.tasks[1].next_fire_after(&after).unwrap();
+        assert_eq!(
+            next,
+            DateTime::parse_from_rfc3339("2025-01-02T09:00:00Z").unwrap()
+        );
+
+        let invalid = json_data.replace("Asia/Tokyo", "Mars/Olympus");
+        assert!(parse_config(&invalid, ConfigFormat::Json).is_err());
+    }
+
+    #[test]
+    fn test_defaults_are_inherited_and_overridden() {
+        let json_data = r#"
+        {
+            "defaults": {
+                "timeout": 60,
+                "webhook_url": "https://example.com/hook",
+                "cwd": "/tmp",
+                "env": {"SHARED": "default", "OVERRIDDEN": "default"}
+            },
+            "tasks": [
+                {
+                    "name": "inherits",
+                    "cron_schedule": "* * * * * *",
+                    "command": "echo"
+                },
+                {
+                    "name": "overrides",
+                    "cron_schedule": "* * * * * *",
+                    "command": "echo",
+                    "timeout": 5,
+                    "cwd": "/var",
+                    "env": {"OVERRIDDEN": "task", "OWN": "task"}
+                }
+            ]
+        }"#;
+
+        let config = parse_config(json_data, ConfigFormat::Json).expect("Should deserialize");
+
+        let inherits = &config.tasks[0];
+        assert_eq!(inherits.timeout, Some(60));
+        assert_eq!(
+            inherits.webhook_url.as_deref(),
+            Some("https://example.com/hook")
+        );
+        assert_eq!(inherits.cwd.as_deref(), Some("/tmp"));
+        assert_eq!(inherits.env.as_ref().unwrap()["SHARED"], "default");
+
+        let overrides = &config.tasks[1];
+        assert_eq!(overrides.timeout, Some(5));
+        assert_eq!(overrides.cwd.as_deref(), Some("/var"));
+        let env = overrides.env.as_ref().unwrap();
+        assert_eq!(env["SHARED"], "default");
+        assert_eq!(env["OVERRIDDEN"], "task");
+        assert_eq!(env["OWN"], "task");
+    }
+
+    #[test]
+    fn test_load_config_dir_merges_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("a.json"),
+            r#"{"tasks": [{"name": "a", "cron_schedule": "* * * * * *", "command": "echo"}]}"#,
+        )
+        .unwrap();
+        std::fs::create_dir(dir.path().join("team")).unwrap();
+        std::fs::write(
+            dir.path().join("team").join("b.toml"),
+            "[defaults]\ntimeout = 5\n\n[[tasks]]\nname = \"b\"\ncron_schedule = \"* * * * * *\"\ncommand = \"echo\"\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("README.md"), "not a config").unwrap();
+        std::fs::write(dir.path().join(".a.json.swp"), "garbage").unwrap();
+
+        let config = load_config(dir.path(), None).expect("Should merge directory");
+        let names: Vec<_> = config.tasks.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["a", "b"]);
+        assert_eq!(config.tasks[0].timeout, None);
+        assert_eq!(config.tasks[1].timeout, Some(5));
+
+        std::fs::write(
+            dir.path().join("c.yaml"),
+            "tasks:\n  - name: a\n    cron_schedule: \"* * * * * *\"\n    command: echo\n",
+        )
+        .unwrap();
+        let err = load_config(dir.path(), None).unwrap_err().to_string();
+        assert!(err.contains("Duplicate task name 'a'"), "{}", err);
+        assert!(err.contains("c.yaml") && err.contains("a.json"), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_error_points_at_task_and_field() {
+        let json_data = r#"{
+  "tasks": [
+    {"name": "ok", "cron_schedule": "* * * * * *", "command": "echo"},
+    {
+      "name": "backup",
+      "cron_schedule": "61 * * * * *",
+      "command": "echo"
+    }
+  ]
+}"#;
+        let err = parse_config(json_data, ConfigFormat::Json).unwrap_err();
+        assert_eq!(err.task.as_deref(), Some("backup"));
+        assert_eq!(err.field.as_deref(), Some("tasks[1].cron_schedule"));
+        assert_eq!(err.position, Some((6, 24)));
+        assert!(err
+            .message
+            .starts_with("invalid cron expression '61 * * * * *'"));
+
+        let rendered = err.to_string();
+        assert!(rendered.contains(r#"6 |       "cron_schedule": "61 * * * * *","#));
+        assert!(rendered.contains("  |                        ^"));
+        assert!(rendered.contains("in task 'backup', field `tasks[1].cron_schedule`"));
+
+        let yaml_data = "tasks:\n  - name: slow\n    cron_schedule: \"* * * * * *\"\n    command: echo\n    timeout: ten\n";
+        let err = parse_config(yaml_data, ConfigFormat::Yaml).unwrap_err();
+        assert_eq!(err.task.as_deref(), Some("slow"));
+        assert_eq!(err.field.as_deref(), Some("tasks[0].timeout"));
+        assert_eq!(err.position, Some((5, 14)));
+        assert!(err.message.starts_with("invalid type"), "{}", err.message);
+
+        let toml_data =
+            "[[tasks]]\nname = \"nightly\"\ncron_schedule = \"bad\"\ncommand = \"echo\"\n";
+        let err = parse_config(toml_data, ConfigFormat::Toml).unwrap_err();
+        assert_eq!(err.task.as_deref(), Some("nightly"));
+        assert_eq!(err.position, Some((3, 17)));
+    }
+
+    #[test]
+    fn test_interpolate() {
+        let lookup = |name: &str| (name == "HOST").then(|| "example.com".to_string());
+
+        assert_eq!(
+            interpolate("https://${HOST}/hook", &lookup).unwrap(),
+            "https://example.com/hook"
+        );
+        assert_eq!(
+            interpolate("${PORT:-8080} ${HOST:-unused}", &lookup).unwrap(),
+            "8080 example.com"
+        );
+        assert_eq!(
+            interpolate("$HOME $${HOST} $", &lookup).unwrap(),
+            "$HOME ${HOST} $"
+        );
+
+        let err = interpolate("${MISSING}", &lookup).unwrap_err();
+        assert_eq!(err.name.as_deref(), Some("MISSING"));
+        assert!(interpolate("${HOST", &lookup).is_err());
+    }
+
+    #[test]
+    fn test_task_fields_are_interpolated() {
+        let env = |name: &str| match name {
+            "CHRONSYNC_TEST_SECRET" => Some("from-env".to_string()),
+            "CHRONSYNC_TEST_REGION" => Some("eu".to_string()),
+            _ => None,
+        };
+        let json_data = r#"{
+  "vars": {"ROOT": "/srv/app", "CHRONSYNC_TEST_SECRET": "from-vars"},
+  "defaults": {"webhook_url": "https://hooks.example.com/${CHRONSYNC_TEST_SECRET}"},
+  "tasks": [
+    {
+      "name": "deploy",
+      "cron_schedule": "* * * * * *",
+      "command": "${ROOT}/bin/deploy",
+      "args": ["--env", "${STAGE:-prod}"],
+      "cwd": "${ROOT}",
+      "env": {"TOKEN": "${CHRONSYNC_TEST_SECRET}", "REGION": "${CHRONSYNC_TEST_REGION}"}
+    }
+  ]
+}"#;
+
+        let config =
+            parse_config_with_env(json_data, ConfigFormat::Json, &env).expect("Should interpolate");
+        let task = &config.tasks[0];
+        assert_eq!(task.command, "/srv/app/bin/deploy");
+        assert_eq!(task.args.as_deref().unwrap(), ["--env", "prod"]);
+        assert_eq!(task.cwd.as_deref(), Some("/srv/app"));
+        assert_eq!(task.env.as_ref().unwrap()["TOKEN"], "from-vars");
+        assert_eq!(task.env.as_ref().unwrap()["REGION"], "eu");
+        assert_eq!(
+            task.webhook_url.as_deref(),
+            Some("https://hooks.example.com/from-vars")
+        );
+
+        let json_data = json_data.replace("${ROOT}/bin", "${CHRONSYNC_TEST_UNDEFINED}/bin");
+        let err = parse_config_with_env(&json_data, ConfigFormat::Json, &env).unwrap_err();
+        assert_eq!(err.message, "undefined variable 'CHRONSYNC_TEST_UNDEFINED'");
+        assert_eq!(err.task.as_deref(), Some("deploy"));
+        assert_eq!(err.field.as_deref(), Some("tasks[0].command"));
+        assert_eq!(err.position, Some((8, 19)));
+    }
+
+    #[test]
+    fn test_notifiers_are_resolved_by_name() {
+        let json_data = r#"{
+  "vars": {"TOKEN": "secret"},
+  "notifiers": {
+    "ops": {"type": "webhook", "url": "https://ops.example.com",
+            "headers": {"Authorization": "Bearer ${TOKEN}"}},
+    "chat": {"type": "discord", "url": "https://discord.example.com/hook"}
+  },
+  "defaults": {"notify": ["chat"]},
+  "tasks": [
+    {"name": "both", "cron_schedule": "* * * * * *", "command": "true",
+     "notify": ["ops", "chat"]},
+    {"name": "inherited", "cron_schedule": "* * * * * *", "command": "true"}
+  ]
+}"#;
+
+        let config = parse_config(json_data, ConfigFormat::Json).expect("Should resolve");
+        let names: Vec<&str> = config.tasks[0]
+            .notifiers
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        assert_eq!(names, ["ops", "chat"]);
+        match &config.tasks[0].notifiers[0].1.target {
+            Target::Webhook { headers, .. } => {
+                assert_eq!(headers["Authorization"], "Bearer secret")
+            }
+            other => panic!("unexpected notifier {:?}", other),
+        }
+        assert_eq!(config.tasks[1].notifiers.len(), 1);
+        assert!(config.tasks[1].has_notify_targets());
+
+        let json_data = json_data.replace(r#"["ops", "chat"]"#, r#"["ops", "pager"]"#);
+        let err = parse_config(&json_data, ConfigFormat::Json).unwrap_err();
+        assert_eq!(err.message, "unknown notifier 'pager'");
+        assert_eq!(err.task.as_deref(), Some("both"));
+        assert_eq!(err.field.as_deref(), Some("tasks[0].notify[1]"));
+    }
+
+    #[test]
+    fn test_out_of_range_throttle_interval_is_rejected() {
+        let json_data = r#"{
+  "defaults": {"notify_throttle": {"window": 10000000000000000}},
+  "tasks": [
+    {"name": "noisy", "cron_schedule": "* * * * * *", "command": "false"}
+  ]
+}"#;
+        let err = parse_config(json_data, ConfigFormat::Json).unwrap_err();
+        assert_eq!(err.message, "10000000000000000 seconds is out of range");
+        assert_eq!(err.task.as_deref(), Some("noisy"));
+        assert_eq!(
+            err.field.as_deref(),
+            Some("tasks[0].notify_throttle.window")
+        );
+        assert_eq!(err.position.map(|(line, _)| line), Some(2));
+
+        let json_data = json_data.replace(
+            r#""window": 10000000000000000"#,
+            r#""digest_interval": 9223372036854775807"#,
+        );
+        let err = parse_config(&json_data, ConfigFormat::Json).unwrap_err();
+        assert_eq!(
+            err.field.as_deref(),
+            Some("tasks[0].notify_throttle.digest_interval")
+        );
+    }
+
+    #[test]
+    fn test_unknown_template_placeholder_is_rejected() {
+        let json_data = r#"{
+  "notifiers": {
+    "chat": {"type": "slack", "url": "https://hooks.example.com",
+             "template": "{{task}} failed on {{host}}"}
+  },
+  "tasks": [
+    {"name": "backup", "cron_schedule": "* * * * * *", "command": "true",
+     "notify": ["chat"], "notify_template": "{{task}}: {{ exitcode }}"}
+  ]
+}"#;
+
+        let err = parse_config(json_data, ConfigFormat::Json).unwrap_err();
+        assert!(err
+            .message
+            .starts_with("unknown placeholder '{{exitcode}}'"));
+        assert_eq!(err.task.as_deref(), Some("backup"));
+        assert_eq!(err.field.as_deref(), Some("tasks[0].notify_template"));
+        assert_eq!(err.position.map(|(line, _)| line), Some(8));
+
+        let json_data = json_data.replace("{{ exitcode }}", "{{exit_code}}");
+        let config = parse_config(&json_data, ConfigFormat::Json).expect("Should parse");
+        assert_eq!(
+            config.tasks[0].notifiers[0].1.template.as_deref(),
+            Some("{{task}} failed on {{host}}")
+        );
     }
 
     #[test]
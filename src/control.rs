@@ -0,0 +1,169 @@
+use crate::scheduler::TaskStatus;
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::{mpsc, oneshot};
+
+/// A request sent to the daemon over the control socket, one JSON object
+/// per line.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum ControlRequest {
+    Status,
+    Trigger { task: String },
+    Pause { task: String },
+    Resume { task: String },
+    Cancel { task: String },
+    Reload,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ControlResponse {
+    pub ok: bool,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tasks: Option<Vec<TaskStatus>>,
+}
+
+impl ControlResponse {
+    pub fn ok(message: impl Into<String>) -> Self {
+        ControlResponse {
+            ok: true,
+            message: message.into(),
+            tasks: None,
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        ControlResponse {
+            ok: false,
+            message: message.into(),
+            tasks: None,
+        }
+    }
+
+    pub fn from_result(result: Result<String, String>) -> Self {
+        match result {
+            Ok(message) => ControlResponse::ok(message),
+            Err(message) => ControlResponse::error(message),
+        }
+    }
+}
+
+/// A request received by the server, handed to the daemon's main loop
+/// together with the channel to answer on.
+pub struct ControlCommand {
+    pub request: ControlRequest,
+    pub reply: oneshot::Sender<ControlResponse>,
+}
+
+/// Binds the control socket at `path`. A stale socket file left by a crashed
+/// daemon is removed, but a socket that still accepts connections is treated
+/// as another running daemon.
+pub async fn bind(path: &Path) -> Result<UnixListener, Box<dyn Error>> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    if path.exists() {
+        if UnixStream::connect(path).await.is_ok() {
+            return Err(format!(
+                "another chronsync daemon is already listening on {}",
+                path.display()
+            )
+            .into());
+        }
+        fs::remove_file(path)?;
+    }
+
+    let listener = UnixListener::bind(path)?;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+
+    info!("[Control] Listening on: {}", path.display());
+
+    Ok(listener)
+}
+
+/// Accepts connections on `listener` and forwards every request to `tx`.
+pub async fn serve(listener: UnixListener, tx: mpsc::Sender<ControlCommand>) {
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(e) => {
+                error!("[Control] Failed to accept connection: {}", e);
+                continue;
+            }
+        };
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(stream, tx).await {
+                warn!("[Control] Connection error: {}", e);
+            }
+        });
+    }
+}
+
+async fn handle_connection(stream: UnixStream, tx: mpsc::Sender<ControlCommand>) -> io::Result<()> {
+    let (read, mut write) = stream.into_split();
+    let mut lines = BufReader::new(read).lines();
+
+    let Some(line) = lines.next_line().await? else {
+        return Ok(());
+    };
+
+    let response = match serde_json::from_str::<ControlRequest>(&line) {
+        Ok(request) => {
+            info!("[Control] Received request: {:?}", request);
+            let (reply, reply_rx) = oneshot::channel();
+            if tx.send(ControlCommand { request, reply }).await.is_err() {
+                ControlResponse::error("daemon is shutting down")
+            } else {
+                reply_rx
+                    .await
+                    .unwrap_or_else(|_| ControlResponse::error("daemon dropped the request"))
+            }
+        }
+        Err(e) => {
+            error!("[Control] Invalid request: {}", e);
+            ControlResponse::error(format!("invalid request: {}", e))
+        }
+    };
+
+    let mut payload = serde_json::to_string(&response)?;
+    payload.push('\n');
+    write.write_all(payload.as_bytes()).await?;
+    write.shutdown().await
+}
+
+/// Sends a single request to the daemon listening on `path`.
+pub async fn send_request(
+    path: &Path,
+    request: &ControlRequest,
+) -> Result<ControlResponse, Box<dyn Error>> {
+    let stream = UnixStream::connect(path).await.map_err(|e| {
+        format!(
+            "could not connect to the daemon at {}: {} (is `chronsync run` running?)",
+            path.display(),
+            e
+        )
+    })?;
+    let (read, mut write) = stream.into_split();
+
+    let mut payload = serde_json::to_string(request)?;
+    payload.push('\n');
+    write.write_all(payload.as_bytes()).await?;
+
+    let mut lines = BufReader::new(read).lines();
+    let line = lines
+        .next_line()
+        .await?
+        .ok_or("the daemon closed the connection without answering")?;
+
+    Ok(serde_json::from_str(&line)?)
+}
@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+/// Parses a dotenv file: one `KEY=VALUE` per line, with optional `export`
+/// prefixes, `#` comments and single or double quoted values.
+pub fn parse(content: &str) -> Result<HashMap<String, String>, String> {
+    let mut vars = HashMap::new();
+
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line).trim_start();
+
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(format!("line {}: expected KEY=VALUE", i + 1));
+        };
+        let key = key.trim();
+        if key.is_empty() || key.contains(char::is_whitespace) {
+            return Err(format!("line {}: invalid variable name '{}'", i + 1, key));
+        }
+
+        vars.insert(key.to_string(), unquote(value.trim()));
+    }
+
+    Ok(vars)
+}
+
+fn unquote(value: &str) -> String {
+    let quoted = |q: char| value.len() >= 2 && value.starts_with(q) && value.ends_with(q);
+
+    if quoted('\'') {
+        value[1..value.len() - 1].to_string()
+    } else if quoted('"') {
+        unescape(&value[1..value.len() - 1])
+    } else {
+        // Unquoted values may carry a trailing comment.
+        match value.find(" #") {
+            Some(i) => value[..i].trim_end().to_string(),
+            None => value.to_string(),
+        }
+    }
+}
+
+/// Decodes `\n`, `\"` and `\\` in a double quoted value. Other
+/// backslashes are kept as they are.
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some(escaped @ ('"' | '\\')) => out.push(escaped),
+            Some(other) => {
+                out.push('\\');
+                out.push(other);
+            }
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+pub fn load(path: &Path) -> Result<HashMap<String, String>, Box<dyn Error>> {
+    let content =
+        fs::read_to_string(path).map_err(|e| format!("env_file {}: {}", path.display(), e))?;
+    parse(&content).map_err(|e| format!("env_file {}: {}", path.display(), e).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dotenv() {
+        let vars = parse(
+            r#"
+# credentials
+export API_TOKEN=abc123
+PLAIN = value # trailing comment
+SINGLE='keep $literal # too'
+DOUBLE="line\nbreak"
+WINDOWS="C:\\new \"dir\" \d"
+EMPTY=
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(vars["API_TOKEN"], "abc123");
+        assert_eq!(vars["PLAIN"], "value");
+        assert_eq!(vars["SINGLE"], "keep $literal # too");
+        assert_eq!(vars["DOUBLE"], "line\nbreak");
+        assert_eq!(vars["WINDOWS"], r#"C:\new "dir" \d"#);
+        assert_eq!(vars["EMPTY"], "");
+
+        assert!(parse("NOT A VAR").is_err());
+    }
+}
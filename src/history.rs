@@ -0,0 +1,362 @@
+use crate::scheduler::{RunContext, RunOutcome, RunResult, RunTrigger};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone};
+use clap::ValueEnum;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// The final state of a single attempt, as stored in the history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    Success,
+    Failed,
+    Timeout,
+    Cancelled,
+    SpawnError,
+}
+
+impl RunStatus {
+    fn from_outcome(outcome: &RunOutcome) -> Self {
+        match outcome {
+            outcome if outcome.is_success() => RunStatus::Success,
+            RunOutcome::TimedOut { .. } => RunStatus::Timeout,
+            RunOutcome::Cancelled { .. } => RunStatus::Cancelled,
+            RunOutcome::SpawnFailed(_) => RunStatus::SpawnError,
+            _ => RunStatus::Failed,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            RunStatus::Success => "success",
+            RunStatus::Failed => "failed",
+            RunStatus::Timeout => "timeout",
+            RunStatus::Cancelled => "cancelled",
+            RunStatus::SpawnError => "spawn_error",
+        }
+    }
+}
+
+/// One line of the history file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryRecord {
+    pub task: String,
+    pub trigger: RunTrigger,
+    pub attempt: u32,
+    pub scheduled_at: Option<DateTime<Local>>,
+    pub started_at: DateTime<Local>,
+    pub ended_at: DateTime<Local>,
+    pub status: RunStatus,
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+    /// Human readable description of how the run ended.
+    pub outcome: String,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl HistoryRecord {
+    pub fn new(task_name: &str, context: &RunContext, attempt: u32, result: &RunResult) -> Self {
+        let exit_code = match result.outcome {
+            RunOutcome::Exited(code) => Some(code),
+            _ => None,
+        };
+
+        HistoryRecord {
+            task: task_name.to_string(),
+            trigger: context.trigger,
+            attempt,
+            scheduled_at: context.scheduled_at,
+            started_at: result.started_at,
+            ended_at: result.ended_at,
+            status: RunStatus::from_outcome(&result.outcome),
+            exit_code,
+            timed_out: matches!(result.outcome, RunOutcome::TimedOut { .. }),
+            outcome: result.outcome.to_string(),
+            stdout: result.stdout.clone(),
+            stderr: result.stderr.clone(),
+        }
+    }
+}
+
+/// How much of the history file is read at a time when searching it from
+/// the end.
+const TAIL_CHUNK: u64 = 64 * 1024;
+
+/// The fields of a record needed to tell how a run ended.
+#[derive(Deserialize)]
+struct RecordStatus {
+    task: String,
+    status: RunStatus,
+}
+
+/// Append-only JSONL store of run records.
+pub struct HistoryStore {
+    path: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl HistoryStore {
+    pub fn new(path: PathBuf) -> Self {
+        HistoryStore {
+            path,
+            lock: Mutex::new(()),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn append(&self, record: &HistoryRecord) -> io::Result<()> {
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+
+        let _guard = self.lock.lock().unwrap();
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(line.as_bytes())
+    }
+
+    /// Loads every record in file order. Lines that fail to parse are
+    /// skipped with a warning.
+    pub fn load(&self) -> io::Result<Vec<HistoryRecord>> {
+        let file = match fs::File::open(&self.path) {
+            Ok(f) => f,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        let mut records = Vec::new();
+        for (index, line) in BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(&line) {
+                Ok(record) => records.push(record),
+                Err(e) => warn!(
+                    "[History] Skipping malformed record at {}:{}: {}",
+                    self.path.display(),
+                    index + 1,
+                    e
+                ),
+            }
+        }
+        Ok(records)
+    }
+
+    /// Status of the latest record of `task` that was not cancelled. The
+    /// file is read backwards from the end, so a task that runs often is
+    /// found without reading the whole history.
+    pub fn last_finished_status(&self, task: &str) -> io::Result<Option<RunStatus>> {
+        let mut file = match fs::File::open(&self.path) {
+            Ok(f) => f,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        // Records are written compactly, so lines of other tasks can be
+        // skipped without parsing them.
+        let needle = format!("\"task\":{}", serde_json::to_string(task)?);
+        let finished_status = |line: &[u8]| {
+            let line = std::str::from_utf8(line).ok()?;
+            if !line.contains(&needle) {
+                return None;
+            }
+            serde_json::from_str::<RecordStatus>(line)
+                .ok()
+                .filter(|r| r.task == task && r.status != RunStatus::Cancelled)
+                .map(|r| r.status)
+        };
+
+        let mut end = file.seek(SeekFrom::End(0))?;
+        // The start of the line the previous chunk began in the middle of.
+        let mut partial = Vec::new();
+        while end > 0 {
+            let start = end.saturating_sub(TAIL_CHUNK);
+            let mut chunk = vec![0; (end - start) as usize];
+            file.seek(SeekFrom::Start(start))?;
+            file.read_exact(&mut chunk)?;
+            chunk.extend_from_slice(&partial);
+            end = start;
+
+            // Unless this is the start of the file, the first line of the
+            // chunk may continue in front of it.
+            let first_line_end = match chunk.iter().position(|b| *b == b'\n') {
+                _ if start == 0 => 0,
+                Some(i) => i + 1,
+                None => {
+                    partial = chunk;
+                    continue;
+                }
+            };
+            for line in chunk[first_line_end..].split(|b| *b == b'\n').rev() {
+                if let Some(status) = finished_status(line) {
+                    return Ok(Some(status));
+                }
+            }
+            chunk.truncate(first_line_end);
+            partial = chunk;
+        }
+        Ok(None)
+    }
+}
+
+/// Criteria for `chronsync history`.
+#[derive(Debug, Default)]
+pub struct HistoryFilter {
+    pub task: Option<String>,
+    pub status: Option<RunStatus>,
+    pub since: Option<DateTime<Local>>,
+    pub until: Option<DateTime<Local>>,
+}
+
+impl HistoryFilter {
+    pub fn matches(&self, record: &HistoryRecord) -> bool {
+        if let Some(task) = &self.task {
+            if &record.task != task {
+                return false;
+            }
+        }
+        if let Some(status) = self.status {
+            if record.status != status {
+                return false;
+            }
+        }
+        if let Some(since) = self.since {
+            if record.started_at < since {
+                return false;
+            }
+        }
+        if let Some(until) = self.until {
+            if record.started_at > until {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Parses a point in time for `--since`/`--until`. Accepts RFC 3339,
+/// `YYYY-MM-DD HH:MM[:SS]` and `YYYY-MM-DD` in local time.
+pub fn parse_time(s: &str) -> Result<DateTime<Local>, String> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Ok(dt.with_timezone(&Local));
+    }
+
+    let naive = ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|fmt| NaiveDateTime::parse_from_str(s, fmt).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+        })
+        .ok_or_else(|| {
+            format!(
+                "invalid time '{}': expected RFC 3339, 'YYYY-MM-DD HH:MM[:SS]' or 'YYYY-MM-DD'",
+                s
+            )
+        })?;
+
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .ok_or_else(|| format!("time '{}' does not exist in the local timezone", s))
+}
+
+pub fn print_table(records: &[HistoryRecord]) {
+    println!(
+        "{:<19}  {:<24}  {:<11}  {:>4}  {:>9}  {:<8}  {:>7}",
+        "STARTED", "TASK", "STATUS", "EXIT", "DURATION", "TRIGGER", "ATTEMPT"
+    );
+    for record in records {
+        let exit = record
+            .exit_code
+            .map(|c| c.to_string())
+            .unwrap_or_else(|| "-".to_string());
+        let duration = (record.ended_at - record.started_at).num_milliseconds() as f64 / 1000.0;
+        println!(
+            "{:<19}  {:<24}  {:<11}  {:>4}  {:>8.2}s  {:<8}  {:>7}",
+            record.started_at.format("%Y-%m-%d %H:%M:%S"),
+            record.task,
+            record.status.as_str(),
+            exit,
+            duration,
+            record.trigger,
+            record.attempt
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_time_formats() {
+        assert!(parse_time("2025-01-02T03:04:05+09:00").is_ok());
+        assert!(parse_time("2025-01-02 03:04:05").is_ok());
+        assert!(parse_time("2025-01-02 03:04").is_ok());
+        assert!(parse_time("2025-01-02").is_ok());
+        assert!(parse_time("yesterday").is_err());
+    }
+
+    #[test]
+    fn test_last_finished_status_reads_from_the_end() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = HistoryStore::new(dir.path().join("history.jsonl"));
+        assert_eq!(store.last_finished_status("backup").unwrap(), None);
+
+        let record = |task: &str, status, output_len| HistoryRecord {
+            task: task.to_string(),
+            trigger: RunTrigger::Schedule,
+            attempt: 1,
+            scheduled_at: None,
+            started_at: Local::now(),
+            ended_at: Local::now(),
+            status,
+            exit_code: None,
+            timed_out: false,
+            outcome: String::new(),
+            stdout: "x".repeat(output_len),
+            stderr: String::new(),
+        };
+        // Large outputs make records span several chunks.
+        store
+            .append(&record("backup", RunStatus::Failed, 100_000))
+            .unwrap();
+        store
+            .append(&record("backup", RunStatus::Cancelled, 10))
+            .unwrap();
+        store
+            .append(&record("other", RunStatus::Success, 150_000))
+            .unwrap();
+        store
+            .append(&record("backup \"2\"", RunStatus::Success, 10))
+            .unwrap();
+
+        assert_eq!(
+            store.last_finished_status("backup").unwrap(),
+            Some(RunStatus::Failed)
+        );
+        assert_eq!(
+            store.last_finished_status("other").unwrap(),
+            Some(RunStatus::Success)
+        );
+        assert_eq!(
+            store.last_finished_status("backup \"2\"").unwrap(),
+            Some(RunStatus::Success)
+        );
+        assert_eq!(store.last_finished_status("missing").unwrap(), None);
+    }
+}
@@ -0,0 +1,297 @@
+use crate::config::{Config, Task};
+use crate::notify::Target;
+use chrono::Local;
+use reqwest::Url;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::env;
+use std::fmt;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+/// Schedules that fire more often than this are reported as a warning.
+const MIN_INTERVAL_SECS: i64 = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        })
+    }
+}
+
+/// One problem found by `check --strict`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Finding {
+    pub severity: Severity,
+    pub task: String,
+    pub message: String,
+}
+
+impl Finding {
+    fn error(task: &Task, message: String) -> Self {
+        Finding {
+            severity: Severity::Error,
+            task: task.name.clone(),
+            message,
+        }
+    }
+
+    fn warning(task: &Task, message: String) -> Self {
+        Finding {
+            severity: Severity::Warning,
+            task: task.name.clone(),
+            message,
+        }
+    }
+}
+
+/// Runs the semantic checks that go beyond parsing. Findings are returned
+/// in task order.
+pub fn lint_config(config: &Config) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let mut seen = HashSet::new();
+
+    for task in &config.tasks {
+        if !seen.insert(task.name.as_str()) {
+            findings.push(Finding::error(
+                task,
+                "duplicate task name; only the first definition is scheduled".to_string(),
+            ));
+        }
+        lint_task(task, &mut findings);
+    }
+
+    findings
+}
+
+fn lint_task(task: &Task, findings: &mut Vec<Finding>) {
+    let command = Path::new(&task.command);
+    if command.components().count() > 1 && command.is_relative() {
+        findings.push(Finding::warning(
+            task,
+            format!(
+                "command '{}' is a relative path and depends on the working directory",
+                task.command
+            ),
+        ));
+    }
+    match resolve_command(task) {
+        Some(path) if !is_executable(&path) => findings.push(Finding::error(
+            task,
+            format!("command '{}' is not executable", path.display()),
+        )),
+        Some(_) => {}
+        None => findings.push(Finding::error(
+            task,
+            format!("command '{}' was not found", task.command),
+        )),
+    }
+
+    if !task.inherit_env.is_empty() && !task.env_clear {
+        findings.push(Finding::warning(
+            task,
+            "inherit_env has no effect without env_clear".to_string(),
+        ));
+    }
+
+    if !task.has_notify_targets() {
+        for (field, set) in [
+            ("notify_on", task.notify_on.is_some()),
+            ("notify_throttle", task.notify_throttle.is_some()),
+        ] {
+            if set {
+                findings.push(Finding::warning(
+                    task,
+                    format!("{} has no effect without webhook_url or notify", field),
+                ));
+            }
+        }
+    }
+    if let Some(throttle) = &task.notify_throttle {
+        if throttle.max_alerts == Some(0) {
+            findings.push(Finding::error(
+                task,
+                "notify_throttle.max_alerts is 0; every notification would be dropped".to_string(),
+            ));
+        }
+    }
+
+    if let Some(cwd) = &task.cwd {
+        if !Path::new(cwd).is_dir() {
+            findings.push(Finding::error(
+                task,
+                format!("cwd '{}' is not an existing directory", cwd),
+            ));
+        }
+    }
+
+    if let Some(url) = &task.webhook_url {
+        lint_url(task, "webhook_url", url, findings);
+    }
+    for (name, notifier) in &task.notifiers {
+        match &notifier.target {
+            Target::Slack { url } | Target::Discord { url } | Target::Webhook { url, .. } => {
+                lint_url(task, &format!("notifier '{}' url", name), url, findings)
+            }
+            Target::Command { .. } => {}
+        }
+    }
+
+    if task.timeout == Some(0) {
+        findings.push(Finding::error(
+            task,
+            "timeout is 0; every run would be killed immediately".to_string(),
+        ));
+    }
+
+    let now = Local::now();
+    match task.next_fire_after(&now) {
+        None => findings.push(Finding::error(
+            task,
+            format!("schedule '{}' never fires", task.cron_schedule),
+        )),
+        Some(first) => {
+            if let Some(second) = task.next_fire_after(&first) {
+                let interval = (second - first).num_seconds();
+                if interval < MIN_INTERVAL_SECS {
+                    findings.push(Finding::warning(
+                        task,
+                        format!(
+                            "schedule '{}' fires every {}s (more often than every {}s)",
+                            task.cron_schedule, interval, MIN_INTERVAL_SECS
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+fn lint_url(task: &Task, field: &str, url: &str, findings: &mut Vec<Finding>) {
+    match Url::parse(url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => {}
+        Ok(parsed) => findings.push(Finding::error(
+            task,
+            format!(
+                "{} '{}' must use http or https, not '{}'",
+                field,
+                url,
+                parsed.scheme()
+            ),
+        )),
+        Err(e) => findings.push(Finding::error(
+            task,
+            format!("{} '{}' is malformed: {}", field, url, e),
+        )),
+    }
+}
+
+/// Finds the file that would be executed for `task.command`, using the
+/// task's own `PATH` if it sets one.
+fn resolve_command(task: &Task) -> Option<PathBuf> {
+    let command = Path::new(&task.command);
+    if command.components().count() > 1 {
+        let path = match &task.cwd {
+            Some(cwd) if command.is_relative() => Path::new(cwd).join(command),
+            _ => command.to_path_buf(),
+        };
+        return path.exists().then_some(path);
+    }
+
+    let search_path = task
+        .env
+        .as_ref()
+        .and_then(|env| env.get("PATH").cloned())
+        .or_else(|| env::var("PATH").ok())?;
+    env::split_paths(&search_path)
+        .map(|dir| dir.join(command))
+        .find(|candidate| candidate.is_file())
+}
+
+fn is_executable(path: &Path) -> bool {
+    path.metadata()
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+pub fn print_findings(findings: &[Finding]) {
+    for finding in findings {
+        println!(
+            "{:<7}  [{}] {}",
+            finding.severity, finding.task, finding.message
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lint(json_data: &str) -> Vec<Finding> {
+        let config: Config = serde_json::from_str(json_data).expect("Should deserialize");
+        lint_config(&config)
+    }
+
+    #[test]
+    fn test_clean_task_has_no_findings() {
+        let findings = lint(
+            r#"{"tasks": [{"name": "ok", "cron_schedule": "0 0 3 * * *", "command": "sh",
+                "cwd": "/", "webhook_url": "https://example.com/hook", "timeout": 10}]}"#,
+        );
+        assert!(findings.is_empty(), "{:?}", findings);
+    }
+
+    #[test]
+    fn test_reports_each_problem() {
+        let findings = lint(
+            r#"{"tasks": [
+                {"name": "dup", "cron_schedule": "0 0 3 * * *", "command": "sh"},
+                {"name": "dup", "cron_schedule": "0 0 3 * * *", "command": "sh"},
+                {"name": "missing", "cron_schedule": "0 0 3 * * *",
+                 "command": "chronsync-no-such-command"},
+                {"name": "relative", "cron_schedule": "0 0 3 * * *", "command": "./run.sh"},
+                {"name": "no_cwd", "cron_schedule": "0 0 3 * * *", "command": "sh",
+                 "cwd": "/chronsync/no/such/dir"},
+                {"name": "bad_url", "cron_schedule": "0 0 3 * * *", "command": "sh",
+                 "webhook_url": "not a url"},
+                {"name": "never", "cron_schedule": "0 0 0 1 1 * 2000", "command": "sh"},
+                {"name": "zero", "cron_schedule": "0 0 3 * * *", "command": "sh", "timeout": 0},
+                {"name": "frequent", "cron_schedule": "*/5 * * * * *", "command": "sh"},
+                {"name": "silent", "cron_schedule": "0 0 3 * * *", "command": "sh",
+                 "notify_on": ["failure"]},
+                {"name": "muted", "cron_schedule": "0 0 3 * * *", "command": "sh",
+                 "webhook_url": "https://example.com/hook",
+                 "notify_throttle": {"max_alerts": 0}}
+            ]}"#,
+        );
+
+        let has = |task: &str, severity: Severity, text: &str| {
+            findings
+                .iter()
+                .any(|f| f.task == task && f.severity == severity && f.message.contains(text))
+        };
+        assert!(has("dup", Severity::Error, "duplicate task name"));
+        assert!(has("missing", Severity::Error, "was not found"));
+        assert!(has("relative", Severity::Warning, "relative path"));
+        assert!(has("no_cwd", Severity::Error, "not an existing directory"));
+        assert!(has("bad_url", Severity::Error, "malformed"));
+        assert!(has("never", Severity::Error, "never fires"));
+        assert!(has("zero", Severity::Error, "timeout is 0"));
+        assert!(has("frequent", Severity::Warning, "fires every 5s"));
+        assert!(has(
+            "silent",
+            Severity::Warning,
+            "without webhook_url or notify"
+        ));
+        assert!(has("muted", Severity::Error, "max_alerts is 0"));
+    }
+}
@@ -1,22 +1,34 @@
 mod config;
+mod control;
+mod dotenv;
+mod history;
+mod lint;
+mod notify;
 mod scheduler;
+mod task_log;
+mod throttle;
 mod utils;
 mod watcher;
-use log::{debug, error, info, LevelFilter};
+use log::{debug, error, LevelFilter};
 use simple_logger::SimpleLogger;
 mod cli;
 mod commands;
 use clap::Parser;
 use cli::{Cli, Commands};
 use commands::{
-    handle_check_command, handle_edit_command, handle_exec_command, handle_init_command,
-    handle_list_command, handle_run_command, handle_service_command,
+    handle_check_command, handle_control_command, handle_edit_command, handle_exec_command,
+    handle_history_command, handle_init_command, handle_list_command, handle_run_command,
+    handle_service_command, handle_status_command,
 };
+use control::ControlRequest;
 use tokio::runtime::Builder;
 
 fn main() {
     let cli = Cli::parse();
-    let log_level = if cli.verbose {
+    // Logs go to stdout, so only errors are logged when it carries JSON.
+    let log_level = if cli.command.prints_json() {
+        LevelFilter::Error
+    } else if cli.verbose {
         LevelFilter::Debug
     } else {
         LevelFilter::Info
@@ -37,7 +49,7 @@ fn main() {
             .map(|n| n.get())
             .unwrap_or(1)
     });
-    info!("Starting Tokio runtime with {} worker threads", num_threads);
+    debug!("Starting Tokio runtime with {} worker threads", num_threads);
 
     builder.worker_threads(num_threads);
 
@@ -75,5 +87,38 @@ async fn async_main(cli: Cli) {
         Commands::Exec(args) => {
             handle_exec_command(args).await;
         }
+        Commands::History(args) => {
+            handle_history_command(args);
+        }
+        Commands::Status(args) => {
+            handle_status_command(args).await;
+        }
+        Commands::Trigger(args) => {
+            let request = ControlRequest::Trigger {
+                task: args.task_name,
+            };
+            handle_control_command(args.socket, request).await;
+        }
+        Commands::Pause(args) => {
+            let request = ControlRequest::Pause {
+                task: args.task_name,
+            };
+            handle_control_command(args.socket, request).await;
+        }
+        Commands::Resume(args) => {
+            let request = ControlRequest::Resume {
+                task: args.task_name,
+            };
+            handle_control_command(args.socket, request).await;
+        }
+        Commands::Cancel(args) => {
+            let request = ControlRequest::Cancel {
+                task: args.task_name,
+            };
+            handle_control_command(args.socket, request).await;
+        }
+        Commands::Reload(args) => {
+            handle_control_command(args.socket, ControlRequest::Reload).await;
+        }
     }
 }
@@ -0,0 +1,816 @@
+use crate::config::{NotifyEvent, Task};
+use crate::scheduler::RunTrigger;
+use chrono::{DateTime, Local};
+use hmac::{Hmac, Mac};
+use log::{error, info, warn};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::fs;
+use std::future::{self, Future};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::process::Stdio;
+use std::sync::OnceLock;
+use std::task::Poll;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::time::{self, sleep, Duration};
+
+/// Default `timeout` in seconds for HTTP notifiers.
+const DEFAULT_HTTP_TIMEOUT: u64 = 10;
+/// Default `timeout` in seconds for `command` notifiers.
+const DEFAULT_COMMAND_TIMEOUT: u64 = 30;
+/// Default number of `retries` after a failed delivery.
+const DEFAULT_RETRIES: u32 = 2;
+/// Delay before the first retry. It doubles after every failed attempt.
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(1);
+
+/// How often the daemon retries spooled notifications.
+pub const SPOOL_RETRY_INTERVAL: Duration = Duration::from_secs(60);
+/// Spooled notifications older than this are dropped.
+const SPOOL_MAX_AGE_HOURS: i64 = 24;
+
+/// Header carrying the HMAC-SHA256 of the request body when a notifier has
+/// a `secret`.
+pub const SIGNATURE_HEADER: &str = "X-Chronsync-Signature";
+
+/// A notification target declared under the top-level `notifiers`: where to
+/// deliver (`type` and its fields) and how.
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+pub struct Notifier {
+    #[serde(flatten)]
+    pub target: Target,
+    /// Message template (see [`render`]).
+    pub template: Option<String>,
+    /// Seconds one delivery attempt may take.
+    pub timeout: Option<u64>,
+    /// Number of retries after a failed delivery, with exponential backoff.
+    pub retries: Option<u32>,
+    /// Key for the `X-Chronsync-Signature` header of HTTP notifiers.
+    pub secret: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Target {
+    /// A Slack incoming webhook (`{"text": ...}`).
+    Slack { url: String },
+    /// A Discord webhook (`{"content": ...}`).
+    Discord { url: String },
+    /// Any HTTP endpoint. The notification is POSTed as JSON, with the
+    /// rendered template in `message`.
+    Webhook {
+        url: String,
+        #[serde(default)]
+        headers: HashMap<String, String>,
+    },
+    /// A local command that receives the notification as JSON on stdin.
+    Command {
+        command: String,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+}
+
+/// Placeholders that can be used as `{{name}}` in message templates.
+pub const PLACEHOLDERS: &[&str] = &[
+    "task",
+    "event",
+    "title",
+    "status",
+    "exit_code",
+    "duration",
+    "attempt",
+    "run_id",
+    "trigger",
+    "scheduled_at",
+    "started_at",
+    "ended_at",
+    "host",
+    "stderr_tail",
+    "repeats",
+];
+
+/// What happened to a run, as sent to notifiers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Notification {
+    pub task: String,
+    pub event: NotifyEvent,
+    /// Human readable description of how the run ended.
+    pub status: String,
+    /// `None` if the process did not exit on its own.
+    pub exit_code: Option<i32>,
+    pub duration_secs: f64,
+    pub run_id: String,
+    pub trigger: RunTrigger,
+    pub attempts: u32,
+    /// The fire time of the run. `None` for manual runs.
+    pub scheduled_at: Option<DateTime<Local>>,
+    pub started_at: DateTime<Local>,
+    pub ended_at: DateTime<Local>,
+    pub host: String,
+    /// The kept tail of stderr, for failed runs.
+    pub stderr: String,
+    /// For a "still failing" digest, the number of consecutive runs that
+    /// ended with `event`.
+    pub repeats: Option<u64>,
+}
+
+impl Notification {
+    fn title(&self) -> String {
+        if let Some(repeats) = self.repeats {
+            return format!("Chronsync Task Still Failing (x{})", repeats);
+        }
+        let title = match self.event {
+            NotifyEvent::Failure => "Chronsync Task Failed",
+            NotifyEvent::Timeout => "Chronsync Task Timed Out",
+            NotifyEvent::SpawnError => "Chronsync Task Failed to Start",
+            NotifyEvent::Recovered => "Chronsync Task Recovered",
+            NotifyEvent::Success => "Chronsync Task Succeeded",
+        };
+        title.to_string()
+    }
+
+    /// The message body shared by the chat notifiers. `bold` is the chat's
+    /// bold marker.
+    fn text(&self, bold: &str) -> String {
+        let mut text = format!(
+            "{b}{}{b}\n\n{b}Task:{b} `{}`\n{b}Status:{b} {}",
+            self.title(),
+            self.task,
+            self.status,
+            b = bold
+        );
+        if self.attempts > 1 {
+            text.push_str(&format!(" (after {} attempts)", self.attempts));
+        }
+        let stderr = self.stderr.trim_end();
+        if !stderr.is_empty() {
+            text.push_str(&format!("\n```\n{}\n```", stderr));
+        }
+        text
+    }
+
+    /// The value of placeholder `name`. Values that do not apply to this
+    /// run, such as `scheduled_at` of a manual run, are empty.
+    fn placeholder(&self, name: &str) -> Option<String> {
+        let value = match name {
+            "task" => self.task.clone(),
+            "event" => self.event.to_string(),
+            "title" => self.title(),
+            "status" => self.status.clone(),
+            "exit_code" => self.exit_code.map(|c| c.to_string()).unwrap_or_default(),
+            "duration" => format!("{:.2}s", self.duration_secs),
+            "attempt" => self.attempts.to_string(),
+            "run_id" => self.run_id.clone(),
+            "trigger" => self.trigger.to_string(),
+            "scheduled_at" => self
+                .scheduled_at
+                .map(|t| t.to_rfc3339())
+                .unwrap_or_default(),
+            "started_at" => self.started_at.to_rfc3339(),
+            "ended_at" => self.ended_at.to_rfc3339(),
+            "host" => self.host.clone(),
+            "stderr_tail" => self.stderr.trim_end().to_string(),
+            "repeats" => self.repeats.map(|n| n.to_string()).unwrap_or_default(),
+            _ => return None,
+        };
+        Some(value)
+    }
+}
+
+/// Returns the names used as `{{name}}` in `template`, trimmed of spaces.
+pub fn placeholders(template: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        names.push(rest[start + 2..start + 2 + end].trim());
+        rest = &rest[start + 2 + end + 2..];
+    }
+    names
+}
+
+/// Returns the first placeholder in `template` that is not one of
+/// [`PLACEHOLDERS`].
+pub fn unknown_placeholder(template: &str) -> Option<&str> {
+    placeholders(template)
+        .into_iter()
+        .find(|name| !PLACEHOLDERS.contains(name))
+}
+
+/// Replaces every `{{name}}` in `template` with its value for
+/// `notification`. Unknown placeholders are left as they are.
+pub fn render(template: &str, notification: &Notification) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let name = rest[start + 2..start + 2 + end].trim();
+        let whole = &rest[start..start + 2 + end + 2];
+        out.push_str(&rest[..start]);
+        match notification.placeholder(name) {
+            Some(value) => out.push_str(&value),
+            None => out.push_str(whole),
+        }
+        rest = &rest[start + 2 + end + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+impl Notifier {
+    /// A notifier with default delivery settings.
+    pub fn new(target: Target) -> Self {
+        Notifier {
+            target,
+            template: None,
+            timeout: None,
+            retries: None,
+            secret: None,
+        }
+    }
+
+    fn timeout(&self) -> Duration {
+        let default = match self.target {
+            Target::Command { .. } => DEFAULT_COMMAND_TIMEOUT,
+            _ => DEFAULT_HTTP_TIMEOUT,
+        };
+        Duration::from_secs(self.timeout.unwrap_or(default))
+    }
+
+    /// Delivers `notification`, retrying failed attempts with exponential
+    /// backoff. Returns the last error once every attempt has failed.
+    pub async fn deliver(
+        &self,
+        notification: &Notification,
+        task_template: Option<&str>,
+    ) -> Result<(), String> {
+        let attempts = self.retries.unwrap_or(DEFAULT_RETRIES).saturating_add(1);
+        let mut delay = RETRY_BASE_DELAY;
+        let mut attempt = 1;
+        loop {
+            match self.send(notification, task_template).await {
+                Ok(()) => return Ok(()),
+                Err(e) if attempt >= attempts => return Err(e),
+                Err(e) => warn!(
+                    "[{}] Notification attempt {}/{} failed: {}. Retrying in {}s...",
+                    notification.task,
+                    attempt,
+                    attempts,
+                    e,
+                    delay.as_secs()
+                ),
+            }
+            sleep(delay).await;
+            delay *= 2;
+            attempt += 1;
+        }
+    }
+
+    /// Makes one delivery attempt. The message is rendered from
+    /// `task_template` if the task sets one, then from the notifier's own
+    /// `template`, and falls back to a built-in summary.
+    pub async fn send(
+        &self,
+        notification: &Notification,
+        task_template: Option<&str>,
+    ) -> Result<(), String> {
+        let message = |bold: &str| match task_template.or(self.template.as_deref()) {
+            Some(template) => render(template, notification),
+            None => notification.text(bold),
+        };
+        let with_message = || {
+            let mut payload = json!(notification);
+            payload["message"] = json!(message(""));
+            payload
+        };
+        let no_headers = HashMap::new();
+
+        let (url, headers, payload) = match &self.target {
+            Target::Slack { url } => (url, &no_headers, json!({ "text": message("*") })),
+            Target::Discord { url } => (url, &no_headers, json!({ "content": message("**") })),
+            Target::Webhook { url, headers } => (url, headers, with_message()),
+            Target::Command { command, args } => {
+                return run_command(command, args, notification, &with_message(), self.timeout())
+                    .await;
+            }
+        };
+        self.post(url, headers, &payload).await
+    }
+
+    async fn post(
+        &self,
+        url: &str,
+        headers: &HashMap<String, String>,
+        payload: &serde_json::Value,
+    ) -> Result<(), String> {
+        let body = serde_json::to_vec(payload).map_err(|e| e.to_string())?;
+        let mut request = client()
+            .post(url)
+            .timeout(self.timeout())
+            .header(reqwest::header::CONTENT_TYPE, "application/json");
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+        if let Some(secret) = &self.secret {
+            request = request.header(SIGNATURE_HEADER, signature(secret, &body));
+        }
+
+        let res = request.body(body).send().await.map_err(|e| e.to_string())?;
+        if res.status().is_success() {
+            Ok(())
+        } else {
+            Err(format!("status {}", res.status()))
+        }
+    }
+}
+
+/// The HTTP client shared by every notification, so connections are reused.
+fn client() -> &'static Client {
+    static CLIENT: OnceLock<Client> = OnceLock::new();
+    CLIENT.get_or_init(Client::new)
+}
+
+/// Returns `sha256=<hex HMAC-SHA256 of body keyed with secret>`.
+pub fn signature(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+async fn run_command(
+    command: &str,
+    args: &[String],
+    notification: &Notification,
+    payload: &serde_json::Value,
+    timeout: Duration,
+) -> Result<(), String> {
+    let mut child = Command::new(command)
+        .args(args)
+        .env("CHRONSYNC_TASK_NAME", &notification.task)
+        .env("CHRONSYNC_EVENT", notification.event.to_string())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("failed to spawn '{}': {}", command, e))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        let payload = serde_json::to_vec(payload).map_err(|e| e.to_string())?;
+        // A command that does not read its input is not an error.
+        let _ = stdin.write_all(&payload).await;
+    }
+
+    match time::timeout(timeout, child.wait()).await {
+        Ok(Ok(status)) if status.success() => Ok(()),
+        Ok(Ok(status)) => Err(format!("'{}' exited with {}", command, status)),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(_) => Err(format!(
+            "'{}' did not finish within {}s",
+            command,
+            timeout.as_secs()
+        )),
+    }
+}
+
+/// Every notify target of `task` by name: its `webhook_url`, treated as a
+/// Slack webhook named `webhook_url`, and each notifier named in `notify`.
+fn targets(task: &Task) -> Vec<(String, Notifier)> {
+    let legacy = task.webhook_url.as_ref().map(|url| {
+        (
+            "webhook_url".to_string(),
+            Notifier::new(Target::Slack { url: url.clone() }),
+        )
+    });
+    legacy.into_iter().chain(task.notifiers.clone()).collect()
+}
+
+/// Delivers `notification` to every target of `task` at once, so a slow
+/// target does not hold up the others. Deliveries that still fail after
+/// their retries are written to `spool`, if given.
+pub async fn notify_all(task: &Task, notification: &Notification, spool: Option<&Spool>) {
+    type Delivery<'a> = Pin<Box<dyn Future<Output = ()> + Send + 'a>>;
+
+    let targets = targets(task);
+    let mut deliveries: Vec<Delivery> = targets
+        .iter()
+        .map(|(name, notifier)| {
+            Box::pin(deliver_or_spool(task, name, notifier, notification, spool)) as Delivery
+        })
+        .collect();
+
+    future::poll_fn(|cx| {
+        deliveries.retain_mut(|delivery| delivery.as_mut().poll(cx).is_pending());
+        if deliveries.is_empty() {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    })
+    .await;
+}
+
+async fn deliver_or_spool(
+    task: &Task,
+    name: &str,
+    notifier: &Notifier,
+    notification: &Notification,
+    spool: Option<&Spool>,
+) {
+    match notifier
+        .deliver(notification, task.notify_template.as_deref())
+        .await
+    {
+        Ok(()) => info!(
+            "[{}] Notification ({}) sent via '{}'.",
+            task.name, notification.event, name
+        ),
+        Err(e) => {
+            error!(
+                "[{}] Failed to send notification via '{}': {}",
+                task.name, name, e
+            );
+            if let Some(spool) = spool {
+                match spool.push(name, notification) {
+                    Ok(path) => info!(
+                        "[{}] Notification spooled to {} for a later retry.",
+                        task.name,
+                        path.display()
+                    ),
+                    Err(e) => error!("[{}] Failed to spool notification: {}", task.name, e),
+                }
+            }
+        }
+    }
+}
+
+/// A notification that could not be delivered, kept on disk until it is.
+#[derive(Debug, Serialize, Deserialize)]
+struct SpoolEntry {
+    notifier: String,
+    spooled_at: DateTime<Local>,
+    notification: Notification,
+}
+
+/// Directory of undelivered notifications, one JSON file each. Entries only
+/// name their notifier; the notifier itself is looked up in the current
+/// configuration when retrying, so secrets never end up on disk.
+pub struct Spool {
+    dir: PathBuf,
+}
+
+impl Spool {
+    pub fn new(dir: PathBuf) -> Self {
+        Spool { dir }
+    }
+
+    fn push(&self, notifier: &str, notification: &Notification) -> io::Result<PathBuf> {
+        fs::create_dir_all(&self.dir)?;
+        let safe_name: String = notifier
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        let path = self
+            .dir
+            .join(format!("{}-{}.json", notification.run_id, safe_name));
+        let entry = SpoolEntry {
+            notifier: notifier.to_string(),
+            spooled_at: Local::now(),
+            notification: notification.clone(),
+        };
+        fs::write(&path, serde_json::to_vec_pretty(&entry)?)?;
+        Ok(path)
+    }
+
+    /// Makes one delivery attempt for every spooled notification, using the
+    /// task definitions in `tasks`. Delivered entries are removed, as are
+    /// entries whose task or notifier no longer exists and entries older
+    /// than a day.
+    pub async fn retry(&self, tasks: &HashMap<String, Task>) {
+        let mut paths: Vec<PathBuf> = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries
+                .filter_map(|e| e.ok().map(|e| e.path()))
+                .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+                .collect(),
+            Err(_) => return,
+        };
+        paths.sort();
+
+        for path in paths {
+            if let Err(reason) = self.retry_entry(&path, tasks).await {
+                warn!(
+                    "[Notify] Dropping spooled notification {}: {}",
+                    path.display(),
+                    reason
+                );
+                let _ = fs::remove_file(&path);
+            }
+        }
+    }
+
+    /// Retries one entry. Returns `Err` with a reason if the entry should be
+    /// dropped; a failed delivery keeps it.
+    async fn retry_entry(&self, path: &Path, tasks: &HashMap<String, Task>) -> Result<(), String> {
+        let content = fs::read(path).map_err(|e| e.to_string())?;
+        let entry: SpoolEntry = serde_json::from_slice(&content).map_err(|e| e.to_string())?;
+        if (Local::now() - entry.spooled_at).num_hours() >= SPOOL_MAX_AGE_HOURS {
+            return Err(format!("older than {} hours", SPOOL_MAX_AGE_HOURS));
+        }
+
+        let task_name = &entry.notification.task;
+        let task = tasks
+            .get(task_name)
+            .ok_or_else(|| format!("task '{}' no longer exists", task_name))?;
+        let (_, notifier) = targets(task)
+            .into_iter()
+            .find(|(name, _)| *name == entry.notifier)
+            .ok_or_else(|| format!("notifier '{}' no longer exists", entry.notifier))?;
+
+        match notifier
+            .send(&entry.notification, task.notify_template.as_deref())
+            .await
+        {
+            Ok(()) => {
+                info!(
+                    "[{}] Spooled notification ({}) sent via '{}'.",
+                    task_name, entry.notification.event, entry.notifier
+                );
+                let _ = fs::remove_file(path);
+            }
+            Err(e) => warn!(
+                "[{}] Spooled notification via '{}' failed again: {}",
+                task_name, entry.notifier, e
+            ),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread::{self, JoinHandle};
+
+    /// A local HTTP stand-in that answers one request per entry of
+    /// `statuses` with that status and returns the requests as text.
+    fn stand_in(statuses: &[u16]) -> (String, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let statuses = statuses.to_vec();
+        let handle = thread::spawn(move || {
+            let mut requests = Vec::new();
+            for status in statuses {
+                let (mut stream, _) = listener.accept().unwrap();
+                stream
+                    .set_read_timeout(Some(std::time::Duration::from_secs(5)))
+                    .unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0u8; 4096];
+                while !request.ends_with(b"}") {
+                    match stream.read(&mut buf) {
+                        Ok(0) | Err(_) => break,
+                        Ok(n) => request.extend_from_slice(&buf[..n]),
+                    }
+                }
+                let response = format!(
+                    "HTTP/1.1 {} X\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    status
+                );
+                stream.write_all(response.as_bytes()).unwrap();
+                requests.push(String::from_utf8_lossy(&request).into_owned());
+            }
+            requests
+        });
+        (url, handle)
+    }
+
+    fn body(request: &str) -> &str {
+        &request[request.find("\r\n\r\n").unwrap() + 4..]
+    }
+
+    fn notification() -> Notification {
+        Notification {
+            task: "backup".to_string(),
+            event: NotifyEvent::Timeout,
+            status: "timed out (terminated by SIGTERM)".to_string(),
+            exit_code: None,
+            duration_secs: 1.5,
+            run_id: "run-1".to_string(),
+            trigger: RunTrigger::Schedule,
+            attempts: 2,
+            scheduled_at: None,
+            started_at: Local::now(),
+            ended_at: Local::now(),
+            host: "db1".to_string(),
+            stderr: "disk busy\n".to_string(),
+            repeats: None,
+        }
+    }
+
+    fn task_with(notifier: Notifier) -> Task {
+        let mut task: Task = serde_json::from_str(
+            r#"{"name": "backup", "cron_schedule": "0 0 0 1 1 *", "command": "true"}"#,
+        )
+        .unwrap();
+        task.notifiers.push(("ops".to_string(), notifier));
+        task
+    }
+
+    #[tokio::test]
+    async fn test_chat_notifiers_use_their_payload_shape() {
+        let (url, requests) = stand_in(&[200]);
+        Notifier::new(Target::Slack { url })
+            .send(&notification(), None)
+            .await
+            .unwrap();
+        let request = &requests.join().unwrap()[0];
+        assert!(
+            body(request).starts_with(r#"{"text":"*Chronsync Task Timed Out*"#),
+            "{}",
+            request
+        );
+        assert!(request.contains("(after 2 attempts)"), "{}", request);
+
+        let (url, requests) = stand_in(&[200]);
+        Notifier::new(Target::Discord { url })
+            .send(&notification(), None)
+            .await
+            .unwrap();
+        let request = &requests.join().unwrap()[0];
+        assert!(
+            body(request).starts_with(r#"{"content":"**Chronsync Task Timed Out**"#),
+            "{}",
+            request
+        );
+        assert!(request.contains("disk busy"), "{}", request);
+    }
+
+    #[tokio::test]
+    async fn test_webhook_notifier_sends_signed_json_with_headers() {
+        let (url, requests) = stand_in(&[200]);
+        let headers = HashMap::from([("X-Token".to_string(), "secret".to_string())]);
+        let mut notifier = Notifier::new(Target::Webhook { url, headers });
+        notifier.template = Some("{{task}} on {{host}}".to_string());
+        notifier.secret = Some("s3cret".to_string());
+        notifier.send(&notification(), None).await.unwrap();
+
+        let request = &requests.join().unwrap()[0];
+        let lower = request.to_lowercase();
+        assert!(lower.contains("x-token: secret"), "{}", request);
+        let expected = signature("s3cret", body(request).as_bytes());
+        assert!(
+            lower.contains(&format!("x-chronsync-signature: {}", expected)),
+            "{}",
+            request
+        );
+
+        let json: serde_json::Value = serde_json::from_str(body(request)).unwrap();
+        assert_eq!(json["task"], "backup");
+        assert_eq!(json["event"], "timeout");
+        assert_eq!(json["attempts"], 2);
+        assert_eq!(json["message"], "backup on db1");
+    }
+
+    #[test]
+    fn test_signature_is_hmac_sha256() {
+        // RFC 4231, test case 2.
+        assert_eq!(
+            signature("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_command_notifier_receives_event_on_stdin() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("event.json");
+        let notifier = Notifier::new(Target::Command {
+            command: "sh".to_string(),
+            args: vec![
+                "-c".to_string(),
+                format!("cat > {}; echo $CHRONSYNC_EVENT >> {0}", out.display()),
+            ],
+        });
+        notifier.send(&notification(), None).await.unwrap();
+
+        let written = std::fs::read_to_string(&out).unwrap();
+        assert!(written.contains(r#""event":"timeout""#), "{}", written);
+        assert!(written.ends_with("}timeout\n"), "{}", written);
+
+        let mut failing = Notifier::new(Target::Command {
+            command: "sleep".to_string(),
+            args: vec!["5".to_string()],
+        });
+        failing.timeout = Some(0);
+        let err = failing.send(&notification(), None).await.unwrap_err();
+        assert!(err.contains("did not finish"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_deliver_retries_failed_attempts() {
+        let (url, requests) = stand_in(&[500, 503, 200]);
+        let notifier = Notifier::new(Target::Slack { url });
+        notifier.deliver(&notification(), None).await.unwrap();
+        assert_eq!(requests.join().unwrap().len(), 3);
+
+        let (url, requests) = stand_in(&[500]);
+        let mut notifier = Notifier::new(Target::Slack { url });
+        notifier.retries = Some(0);
+        let err = notifier.deliver(&notification(), None).await.unwrap_err();
+        assert!(err.contains("500"), "{}", err);
+        requests.join().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_targets_are_notified_concurrently() {
+        let hung = || {
+            let mut notifier = Notifier::new(Target::Command {
+                command: "sleep".to_string(),
+                args: vec!["5".to_string()],
+            });
+            notifier.timeout = Some(1);
+            notifier.retries = Some(0);
+            notifier
+        };
+        let mut task = task_with(hung());
+        task.notifiers.push(("pager".to_string(), hung()));
+
+        let started = std::time::Instant::now();
+        notify_all(&task, &notification(), None).await;
+        assert!(started.elapsed() < std::time::Duration::from_millis(1800));
+    }
+
+    #[tokio::test]
+    async fn test_undelivered_notification_is_spooled_and_retried() {
+        let dir = tempfile::tempdir().unwrap();
+        let spool = Spool::new(dir.path().join("spool"));
+
+        let (url, requests) = stand_in(&[500]);
+        let mut notifier = Notifier::new(Target::Slack { url });
+        notifier.retries = Some(0);
+        notify_all(&task_with(notifier), &notification(), Some(&spool)).await;
+        requests.join().unwrap();
+        let spooled: Vec<_> = fs::read_dir(dir.path().join("spool")).unwrap().collect();
+        assert_eq!(spooled.len(), 1);
+
+        // The retry uses the notifier as currently configured.
+        let (url, requests) = stand_in(&[200]);
+        let tasks = HashMap::from([(
+            "backup".to_string(),
+            task_with(Notifier::new(Target::Slack { url })),
+        )]);
+        spool.retry(&tasks).await;
+        assert!(requests.join().unwrap()[0].contains("Chronsync Task Timed Out"));
+        assert_eq!(fs::read_dir(dir.path().join("spool")).unwrap().count(), 0);
+
+        // Entries whose task is gone are dropped.
+        spool.push("ops", &notification()).unwrap();
+        spool.retry(&HashMap::new()).await;
+        assert_eq!(fs::read_dir(dir.path().join("spool")).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_render_template() {
+        let rendered = render(
+            "[{{ host }}] {{task}} {{event}} after {{duration}} (exit {{exit_code}}, \
+             attempt {{attempt}}, scheduled {{scheduled_at}}){{unknown}} {{",
+            &notification(),
+        );
+        assert_eq!(
+            rendered,
+            "[db1] backup timeout after 1.50s (exit , attempt 2, scheduled ){{unknown}} {{"
+        );
+
+        let digest = Notification {
+            repeats: Some(123),
+            ..notification()
+        };
+        assert_eq!(
+            render("{{title}}: {{repeats}}", &digest),
+            "Chronsync Task Still Failing (x123): 123"
+        );
+
+        assert_eq!(
+            placeholders("{{task}} {{ stderr_tail }}"),
+            ["task", "stderr_tail"]
+        );
+        assert_eq!(
+            unknown_placeholder("{{task}} {{exitcode}}"),
+            Some("exitcode")
+        );
+        assert_eq!(unknown_placeholder("{{task}}"), None);
+    }
+}
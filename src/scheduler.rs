@@ -1,259 +1,1991 @@
-use crate::config::{Config, Task};
-use cron::Schedule;
+use crate::config::{Config, NotifyEvent, OverlapPolicy, Task};
+use crate::history::{HistoryRecord, HistoryStore, RunStatus};
+use crate::notify::{self, Notification, Spool};
+use crate::task_log::TaskLog;
+use crate::throttle::{AlertThrottle, Verdict};
+use crate::utils;
+use chrono::{DateTime, Local};
 use log::{error, info, warn};
-use reqwest::Client;
-use serde_json::json;
-use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
-use tokio::process::Command;
-use tokio::task::JoinHandle;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::env;
+use std::fmt;
+use std::future::Future;
+use std::os::unix::process::ExitStatusExt;
+use std::path::PathBuf;
+use std::process::ExitStatus;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Instant;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::process::{Child, Command};
+use tokio::sync::{mpsc, oneshot, watch};
+use tokio::task::{JoinHandle, JoinSet};
 use tokio::time::{self, sleep, Duration};
 
-type JobHandle = Arc<Mutex<Option<JoinHandle<()>>>>;
+struct Job {
+    task: Task,
+    handle: JoinHandle<()>,
+    status: Arc<Mutex<TaskStatus>>,
+    commands: mpsc::Sender<JobCommand>,
+    retire: oneshot::Sender<Retire>,
+}
+
+/// Tells a job loop that its task was changed or removed. A changed task's
+/// loop hands its runs to the replacement loop through the given sender; a
+/// removed task's loop (`None`) cancels them.
+type Retire = Option<oneshot::Sender<Handover>>;
+
+/// The runs a job loop passes on to the loop that replaces it.
+struct Handover {
+    runs: JoinSet<()>,
+    queued: VecDeque<RunContext>,
+    cancel: watch::Sender<u64>,
+}
+
+/// Requests sent from the scheduler to a running job loop.
+enum JobCommand {
+    Trigger(oneshot::Sender<String>),
+    Cancel(oneshot::Sender<String>),
+}
+
+/// A snapshot of a task's state, as reported by `chronsync status`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskStatus {
+    pub name: String,
+    pub schedule: String,
+    pub paused: bool,
+    pub running: usize,
+    pub queued: usize,
+    pub next_run: Option<DateTime<Local>>,
+    pub last_started_at: Option<DateTime<Local>>,
+    pub last_outcome: Option<String>,
+    pub last_success: Option<bool>,
+}
+
+impl TaskStatus {
+    fn new(task: &Task, paused: bool) -> Self {
+        TaskStatus {
+            name: task.name.clone(),
+            schedule: task.cron_schedule.to_string(),
+            paused,
+            running: 0,
+            queued: 0,
+            next_run: None,
+            last_started_at: None,
+            last_outcome: None,
+            last_success: None,
+        }
+    }
+}
+
+/// Tracks the PIDs of every child process spawned by `execute_command`, so
+/// they can be signalled on shutdown.
+#[derive(Clone, Default)]
+pub struct ChildRegistry {
+    pids: Arc<Mutex<HashMap<u32, String>>>,
+}
+
+/// Removes a child from the registry when its run finishes or is aborted.
+/// If the run is aborted before the child exits, the whole process group is
+/// killed, since `kill_on_drop` only reaches the direct child.
+struct ChildGuard {
+    registry: ChildRegistry,
+    pid: u32,
+    finished: bool,
+}
+
+impl Drop for ChildGuard {
+    fn drop(&mut self) {
+        self.registry.pids.lock().unwrap().remove(&self.pid);
+        if !self.finished {
+            let _ = signal_group(self.pid, libc::SIGKILL);
+        }
+    }
+}
+
+/// Sends `signal` to the process group led by `pid`.
+fn signal_group(pid: u32, signal: libc::c_int) -> std::io::Result<()> {
+    // SAFETY: kill(2) has no memory-safety preconditions.
+    let rc = unsafe { libc::kill(-(pid as libc::pid_t), signal) };
+    if rc == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
+
+pub const DEFAULT_KILL_GRACE: u64 = 5;
+
+/// How long shutdown waits for runs to finish after the grace period has
+/// expired and their processes were killed.
+const SHUTDOWN_KILL_WAIT: Duration = Duration::from_secs(3);
+
+/// How many runs `overlap: queue` keeps waiting behind the one in progress.
+const MAX_QUEUED_RUNS: usize = 10;
+
+/// Returns the environment a run starts from when it should not simply
+/// inherit the daemon's: the login shell's with `login_env`, filtered down to
+/// `inherit_env` with `env_clear`. `None` means "inherit as usual".
+fn base_environment(task: &Task) -> Option<HashMap<String, String>> {
+    if !task.env_clear && !task.login_env {
+        return None;
+    }
+
+    let mut source: HashMap<String, String> = env::vars().collect();
+    if task.login_env {
+        match utils::login_environment() {
+            Some(login) => source = login.clone(),
+            None => warn!(
+                "[{}] Login environment unavailable. Using the daemon's environment.",
+                task.name
+            ),
+        }
+    }
+
+    if task.env_clear {
+        source.retain(|key, _| task.inherit_env.contains(key));
+    }
+    Some(source)
+}
+
+/// Sends SIGTERM to the child's process group and waits up to `kill_grace`
+/// for the child to exit, then sends SIGKILL. Returns true if SIGKILL was
+/// needed.
+async fn terminate_group(name: &str, child: &mut Child, kill_grace: Duration) -> bool {
+    let Some(pid) = child.id() else {
+        return false;
+    };
+
+    if let Err(e) = signal_group(pid, libc::SIGTERM) {
+        error!(
+            "[{}] Failed to send SIGTERM to process group {}: {}",
+            name, pid, e
+        );
+    }
+
+    if time::timeout(kill_grace, child.wait()).await.is_ok() {
+        info!("[{}] Process group exited after SIGTERM.", name);
+        return false;
+    }
+
+    error!(
+        "[{}] Process group still running after {}s. Sending SIGKILL.",
+        name,
+        kill_grace.as_secs()
+    );
+    if let Err(e) = signal_group(pid, libc::SIGKILL) {
+        error!(
+            "[{}] Failed to send SIGKILL to process group {}: {}",
+            name, pid, e
+        );
+    }
+    let _ = child.wait().await;
+    true
+}
+
+/// How a run ended.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RunOutcome {
+    /// The process exited on its own with this exit code.
+    Exited(i32),
+    /// The process was terminated by a signal it did not send itself.
+    Signaled(i32),
+    /// The run hit its timeout. `killed` is true when the process group
+    /// ignored SIGTERM and had to be killed with SIGKILL.
+    TimedOut { killed: bool },
+    /// The run was cancelled through the control socket.
+    Cancelled { killed: bool },
+    /// The command could not be started.
+    SpawnFailed(String),
+    /// Waiting on the process failed.
+    WaitFailed(String),
+}
+
+impl RunOutcome {
+    fn from_wait(result: std::io::Result<ExitStatus>) -> Self {
+        match result {
+            Ok(status) => match (status.code(), status.signal()) {
+                (Some(code), _) => RunOutcome::Exited(code),
+                (None, Some(signal)) => RunOutcome::Signaled(signal),
+                (None, None) => RunOutcome::WaitFailed(format!("unknown exit status: {}", status)),
+            },
+            Err(e) => RunOutcome::WaitFailed(e.to_string()),
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        matches!(self, RunOutcome::Exited(0))
+    }
+}
+
+impl fmt::Display for RunOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunOutcome::Exited(code) => write!(f, "exit code: {}", code),
+            RunOutcome::Signaled(signal) => write!(f, "killed by signal {}", signal),
+            RunOutcome::TimedOut { killed: false } => {
+                write!(f, "timed out (terminated by SIGTERM)")
+            }
+            RunOutcome::TimedOut { killed: true } => {
+                write!(f, "timed out (killed by SIGKILL after kill grace)")
+            }
+            RunOutcome::Cancelled { killed: false } => {
+                write!(f, "cancelled (terminated by SIGTERM)")
+            }
+            RunOutcome::Cancelled { killed: true } => {
+                write!(f, "cancelled (killed by SIGKILL after kill grace)")
+            }
+            RunOutcome::SpawnFailed(e) => write!(f, "failed to spawn: {}", e),
+            RunOutcome::WaitFailed(e) => write!(f, "failed to wait: {}", e),
+        }
+    }
+}
+
+/// The result of a single run returned by `execute_command`.
+#[derive(Debug, Clone)]
+pub struct RunResult {
+    pub outcome: RunOutcome,
+    pub started_at: DateTime<Local>,
+    pub ended_at: DateTime<Local>,
+    pub duration: Duration,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Why a run was started.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RunTrigger {
+    /// The task's cron schedule fired.
+    Schedule,
+    /// The run was requested by a user, e.g. through `chronsync exec`.
+    Manual,
+}
+
+impl fmt::Display for RunTrigger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunTrigger::Schedule => write!(f, "schedule"),
+            RunTrigger::Manual => write!(f, "manual"),
+        }
+    }
+}
+
+/// Describes a single logical run of a task, shared by all its attempts.
+#[derive(Debug, Clone)]
+pub struct RunContext {
+    /// Identifies the run across its attempts.
+    pub run_id: String,
+    /// The fire time the run belongs to. `None` for manual runs.
+    pub scheduled_at: Option<DateTime<Local>>,
+    pub trigger: RunTrigger,
+}
+
+impl RunContext {
+    pub fn scheduled(at: DateTime<Local>) -> Self {
+        RunContext {
+            run_id: next_run_id(),
+            scheduled_at: Some(at),
+            trigger: RunTrigger::Schedule,
+        }
+    }
+
+    pub fn manual() -> Self {
+        RunContext {
+            run_id: next_run_id(),
+            scheduled_at: None,
+            trigger: RunTrigger::Manual,
+        }
+    }
+
+    /// Variables exported to the child process of attempt `attempt`, so
+    /// scripts can tell why and for which fire time they were started.
+    fn env_vars(
+        &self,
+        task_name: &str,
+        attempt: u32,
+        started_at: &DateTime<Local>,
+    ) -> Vec<(&'static str, String)> {
+        let trigger = if attempt > 1 {
+            "retry".to_string()
+        } else {
+            self.trigger.to_string()
+        };
+        let mut vars = vec![
+            ("CHRONSYNC_TASK_NAME", task_name.to_string()),
+            ("CHRONSYNC_RUN_ID", self.run_id.clone()),
+            ("CHRONSYNC_STARTED_AT", started_at.to_rfc3339()),
+            ("CHRONSYNC_ATTEMPT", attempt.to_string()),
+            ("CHRONSYNC_TRIGGER", trigger),
+        ];
+        if let Some(at) = self.scheduled_at {
+            vars.push(("CHRONSYNC_SCHEDULED_AT", at.to_rfc3339()));
+        }
+        vars
+    }
+}
+
+/// Returns an ID that is unique within this daemon and sorts by start time.
+fn next_run_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    format!(
+        "{}-{}",
+        Local::now().format("%Y%m%dT%H%M%S%.3f"),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+/// How long to keep reading a run's output after the process has exited.
+/// Background processes that inherited the pipes would otherwise keep the
+/// run open forever.
+const OUTPUT_DRAIN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Default for `max_output_bytes`.
+pub const DEFAULT_MAX_OUTPUT_BYTES: usize = 4096;
+
+/// Lines longer than this are logged in pieces, so output without newlines
+/// cannot grow the line buffer without bound.
+const MAX_LOG_LINE_BYTES: usize = 8192;
+
+/// Keeps the last `capacity` bytes written to a pipe. The tail is kept,
+/// since that is where errors usually are.
+#[derive(Debug)]
+struct OutputBuffer {
+    data: VecDeque<u8>,
+    capacity: usize,
+    truncated: bool,
+}
+
+impl OutputBuffer {
+    fn new(capacity: usize) -> Self {
+        OutputBuffer {
+            data: VecDeque::new(),
+            capacity,
+            truncated: false,
+        }
+    }
+
+    fn push(&mut self, bytes: &[u8]) {
+        let bytes = if bytes.len() > self.capacity {
+            self.truncated = true;
+            &bytes[bytes.len() - self.capacity..]
+        } else {
+            bytes
+        };
+        let overflow = (self.data.len() + bytes.len()).saturating_sub(self.capacity);
+        if overflow > 0 {
+            self.truncated = true;
+            self.data.drain(..overflow);
+        }
+        self.data.extend(bytes);
+    }
+
+    fn contents(&self) -> String {
+        let (front, back) = self.data.as_slices();
+        let bytes = [front, back].concat();
+        // The cut may have landed inside a multi-byte character.
+        let start = if self.truncated {
+            bytes
+                .iter()
+                .position(|b| b & 0b1100_0000 != 0b1000_0000)
+                .unwrap_or(bytes.len())
+        } else {
+            0
+        };
+        let text = String::from_utf8_lossy(&bytes[start..]);
+        if self.truncated {
+            format!("...[truncated]\n{}", text)
+        } else {
+            text.into_owned()
+        }
+    }
+}
+
+/// Reads a pipe until EOF, writing each line to the task's log file if it
+/// has one and to the daemon log otherwise, and keeping the tail in
+/// `buffer`.
+async fn stream_output<R: AsyncRead + Unpin>(
+    mut reader: R,
+    task_name: String,
+    stream: &'static str,
+    buffer: Arc<Mutex<OutputBuffer>>,
+    task_log: Option<Arc<Mutex<TaskLog>>>,
+) {
+    let log_line = |line: &[u8]| {
+        let line = String::from_utf8_lossy(line);
+        let line = line.trim_end_matches(['\r', '\n']);
+        match &task_log {
+            Some(task_log) if stream == "stderr" => task_log
+                .lock()
+                .unwrap()
+                .write_line(&format!("[stderr] {}", line)),
+            Some(task_log) => task_log.lock().unwrap().write_line(line),
+            None => info!("[{}] {}: {}", task_name, stream, line),
+        }
+    };
+
+    let mut chunk = [0u8; 8192];
+    let mut pending = Vec::new();
+    loop {
+        let n = match reader.read(&mut chunk).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => n,
+        };
+        buffer.lock().unwrap().push(&chunk[..n]);
+
+        pending.extend_from_slice(&chunk[..n]);
+        let mut start = 0;
+        while let Some(pos) = pending[start..].iter().position(|&b| b == b'\n') {
+            log_line(&pending[start..start + pos]);
+            start += pos + 1;
+        }
+        pending.drain(..start);
+        if pending.len() >= MAX_LOG_LINE_BYTES {
+            log_line(&pending);
+            pending.clear();
+        }
+    }
+    if !pending.is_empty() {
+        log_line(&pending);
+    }
+}
+
+/// A pipe being streamed by `stream_output`.
+struct OutputReader {
+    handle: JoinHandle<()>,
+    buffer: Arc<Mutex<OutputBuffer>>,
+}
+
+impl OutputReader {
+    fn spawn<R: AsyncRead + Unpin + Send + 'static>(
+        reader: R,
+        task: &Task,
+        stream: &'static str,
+        task_log: Option<Arc<Mutex<TaskLog>>>,
+    ) -> Self {
+        let capacity = task.max_output_bytes.unwrap_or(DEFAULT_MAX_OUTPUT_BYTES);
+        let buffer = Arc::new(Mutex::new(OutputBuffer::new(capacity)));
+        let handle = tokio::spawn(stream_output(
+            reader,
+            task.name.clone(),
+            stream,
+            buffer.clone(),
+            task_log,
+        ));
+        OutputReader { handle, buffer }
+    }
+
+    /// Waits for the pipe to close and returns what was kept. Output read
+    /// before the drain timeout is kept even if the pipe stays open.
+    async fn collect(self) -> String {
+        let abort = self.handle.abort_handle();
+        if time::timeout(OUTPUT_DRAIN_TIMEOUT, self.handle)
+            .await
+            .is_err()
+        {
+            abort.abort();
+        }
+        let contents = self.buffer.lock().unwrap().contents();
+        contents
+    }
+}
+
+async fn collect_output(reader: Option<OutputReader>) -> String {
+    match reader {
+        Some(reader) => reader.collect().await,
+        None => String::new(),
+    }
+}
+
+fn close_task_log(task_log: Option<Arc<Mutex<TaskLog>>>, context: &RunContext, result: &RunResult) {
+    if let Some(task_log) = task_log {
+        task_log.lock().unwrap().write_line(&format!(
+            "=== run {} finished: {} ({:.2}s) ===",
+            context.run_id,
+            result.outcome,
+            result.duration.as_secs_f64()
+        ));
+    }
+}
+
+/// Waits for every handle that has not finished yet. Can be called again
+/// after being interrupted.
+async fn join_all(handles: &mut [JoinHandle<()>]) {
+    for handle in handles.iter_mut() {
+        if !handle.is_finished() {
+            let _ = handle.await;
+        }
+    }
+}
+
+/// Everything a run needs besides the task itself: the child registry used
+/// for shutdown, the history store, the notification spool and throttle and
+/// the shutdown flag.
+#[derive(Clone)]
+pub struct Runner {
+    children: ChildRegistry,
+    history: Option<Arc<HistoryStore>>,
+    spool: Option<Arc<Spool>>,
+    /// The shutdown deadline, once the scheduler is shutting down.
+    stopping: watch::Receiver<Option<time::Instant>>,
+    /// Whether the latest finished run of each task failed, for `recovered`
+    /// notifications.
+    last_failed: Arc<Mutex<HashMap<String, bool>>>,
+    alerts: Arc<AlertThrottle>,
+    /// Notifications still being delivered. Runs do not wait for them.
+    deliveries: Arc<Mutex<JoinSet<()>>>,
+    /// Open task log files by path. Every run writing to a file shares one
+    /// `TaskLog`, so its size is tracked and it is rotated in one place.
+    task_logs: Arc<Mutex<HashMap<PathBuf, Weak<Mutex<TaskLog>>>>>,
+}
+
+impl ChildRegistry {
+    fn register(&self, pid: u32, task_name: &str) -> ChildGuard {
+        self.pids.lock().unwrap().insert(pid, task_name.to_string());
+        ChildGuard {
+            registry: self.clone(),
+            pid,
+            finished: false,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.pids.lock().unwrap().len()
+    }
+
+    /// Sends `signal` to the process group of every running child.
+    pub fn signal_all(&self, signal: libc::c_int) {
+        for (pid, task_name) in self.pids.lock().unwrap().iter() {
+            if let Err(e) = signal_group(*pid, signal) {
+                warn!(
+                    "[{}] Failed to send signal {} to process group {}: {}",
+                    task_name, signal, pid, e
+                );
+            }
+        }
+    }
+}
 
 pub struct TaskScheduler {
-    job_handles: Vec<JobHandle>,
+    jobs: HashMap<String, Job>,
+    children: ChildRegistry,
+    stopping: watch::Sender<Option<time::Instant>>,
+    runner: Runner,
 }
 
 impl TaskScheduler {
-    pub fn new() -> Self {
+    pub fn new(history: Option<HistoryStore>, spool: Option<Spool>) -> Self {
+        let children = ChildRegistry::default();
+        let stopping = watch::Sender::new(None);
+        let runner = Runner::new(children.clone(), history, spool, stopping.subscribe());
+
         TaskScheduler {
-            job_handles: Vec::new(),
+            jobs: HashMap::new(),
+            children,
+            stopping,
+            runner,
+        }
+    }
+
+    /// Stops scheduling new runs, cancels every run in progress the same way
+    /// `chronsync cancel` does and waits up to `grace` for them to finish.
+    /// The runs get all of `grace` to exit after SIGTERM, regardless of their
+    /// `kill_grace`. Anything still running after that is killed with SIGKILL.
+    pub async fn shutdown(&mut self, grace: Duration) {
+        info!("[Scheduler] Shutting down. No new runs will be scheduled.");
+        let deadline = time::Instant::now() + grace;
+        self.stopping.send_replace(Some(deadline));
+
+        let running = self.children.len();
+        if running > 0 {
+            info!(
+                "[Scheduler] Cancelling {} running process(es). Waiting up to {}s...",
+                running,
+                grace.as_secs()
+            );
+        }
+
+        let mut handles: Vec<JoinHandle<()>> =
+            self.jobs.drain().map(|(_, job)| job.handle).collect();
+
+        if time::timeout(grace, join_all(&mut handles)).await.is_err() {
+            warn!(
+                "[Scheduler] Grace period expired. Sending SIGKILL to {} remaining process(es).",
+                self.children.len()
+            );
+            self.children.signal_all(libc::SIGKILL);
+            // Give the killed runs a moment to be recorded before dropping
+            // whatever is left.
+            if time::timeout(SHUTDOWN_KILL_WAIT, join_all(&mut handles))
+                .await
+                .is_err()
+            {
+                for handle in &handles {
+                    handle.abort();
+                }
+            }
+        }
+
+        info!("[Scheduler] All tasks stopped.");
+
+        if time::timeout_at(deadline, self.runner.wait_for_notifications())
+            .await
+            .is_err()
+        {
+            warn!(
+                "[Scheduler] Grace period expired. Abandoning notifications still being delivered."
+            );
         }
     }
 
+    /// Applies a new configuration by diffing it against the running jobs.
+    /// Only tasks that were added, removed or changed are touched; unchanged
+    /// tasks keep running, including any in-flight command. Runs in progress
+    /// of a changed task finish with the previous definition and count
+    /// towards the new one's overlap policy. Those of a removed task are
+    /// cancelled.
     pub fn reload_tasks(&mut self, config: Config) {
-        info!(
-            "[Scheduler] Stopping {} existing tasks...",
-            self.job_handles.len()
-        );
+        let mut incoming: HashMap<String, Task> = HashMap::new();
+        let mut order: Vec<String> = Vec::new();
+
+        for task in config.tasks {
+            if incoming.contains_key(&task.name) {
+                warn!(
+                    "[Scheduler] Duplicate task name '{}'. Ignoring the later definition.",
+                    task.name
+                );
+                continue;
+            }
+            order.push(task.name.clone());
+            incoming.insert(task.name.clone(), task);
+        }
+
+        let mut removed: Vec<String> = self
+            .jobs
+            .keys()
+            .filter(|name| !incoming.contains_key(*name))
+            .cloned()
+            .collect();
+        removed.sort();
 
-        for handle_mutex in self.job_handles.drain(..) {
-            if let Some(handle) = handle_mutex.lock().unwrap().take() {
-                handle.abort();
+        let mut cancelled = 0;
+        for name in &removed {
+            if let Some(job) = self.jobs.remove(name) {
+                info!("[Scheduler] Stopping removed task '{}'", name);
+                cancelled += job.status.lock().unwrap().running;
+                let _ = job.retire.send(None);
+            }
+        }
+
+        let mut added: Vec<String> = Vec::new();
+        let mut changed: Vec<String> = Vec::new();
+        let mut unchanged: Vec<String> = Vec::new();
+        let mut kept = 0;
+
+        for name in order {
+            let task = incoming.remove(&name).unwrap();
+
+            match self.jobs.get(&name) {
+                Some(job) if job.task == task => {
+                    unchanged.push(name);
+                }
+                Some(_) => {
+                    info!("[Scheduler] Restarting changed task '{}'", name);
+                    let old = self.jobs.remove(&name);
+                    kept += old
+                        .as_ref()
+                        .map_or(0, |old| old.status.lock().unwrap().running);
+                    self.register_task(task, old);
+                    changed.push(name);
+                }
+                None => {
+                    self.register_task(task, None);
+                    added.push(name);
+                }
             }
         }
 
         info!(
-            "[Scheduler] Existing tasks stopped. Registering {} new tasks...",
-            config.tasks.len()
+            "[Scheduler] Reload summary: {} added, {} removed, {} changed, {} unchanged.",
+            added.len(),
+            removed.len(),
+            changed.len(),
+            unchanged.len()
         );
-
-        for task in config.tasks {
-            self.register_task(task);
+        if !added.is_empty() {
+            info!("[Scheduler]   Added: {:?}", added);
+        }
+        if !removed.is_empty() {
+            info!("[Scheduler]   Removed: {:?}", removed);
+        }
+        if cancelled > 0 {
+            info!(
+                "[Scheduler]   Cancelling {} run(s) of removed tasks.",
+                cancelled
+            );
+        }
+        if !changed.is_empty() {
+            info!("[Scheduler]   Changed: {:?}", changed);
+        }
+        if kept > 0 {
+            info!(
+                "[Scheduler]   {} run(s) of changed tasks continue with the previous definition.",
+                kept
+            );
         }
     }
 
-    fn register_task(&mut self, task: Task) {
-        let schedule = task.cron_schedule.clone();
+    /// Starts the job loop of `task`. When it replaces the job of a changed
+    /// task, the new loop takes over the old one's runs and status.
+    fn register_task(&mut self, task: Task, previous: Option<Job>) {
         let name = task.name.clone();
 
-        let handle_ref: JobHandle = Arc::new(Mutex::new(None));
-
-        let handle_ref_for_job = handle_ref.clone();
+        info!(
+            "[Scheduler] Registering task '{}' with schedule: {} (timezone: {}, overlap: {:?})",
+            name,
+            task.cron_schedule,
+            task.timezone
+                .map(|tz| tz.to_string())
+                .unwrap_or_else(|| "local".to_string()),
+            task.overlap
+        );
 
-        self.job_handles.push(handle_ref.clone());
+        let (status, predecessor) = match previous {
+            Some(old) => {
+                let (successor, predecessor) = oneshot::channel();
+                let _ = old.retire.send(Some(successor));
+                old.status.lock().unwrap().schedule = task.cron_schedule.to_string();
+                (old.status, Some(predecessor))
+            }
+            None => (Arc::new(Mutex::new(TaskStatus::new(&task, false))), None),
+        };
+        let (commands, commands_rx) = mpsc::channel(8);
+        let (retire, retire_rx) = oneshot::channel();
+        let job_loop = JobLoop::new(
+            Arc::new(task.clone()),
+            self.runner.clone(),
+            status.clone(),
+            commands_rx,
+        );
+        let handle = tokio::spawn(job_loop.run(predecessor, retire_rx));
 
-        info!(
-            "[Scheduler] Registering task '{}' with schedule: {}",
-            name, schedule
+        self.jobs.insert(
+            name,
+            Job {
+                task,
+                handle,
+                status,
+                commands,
+                retire,
+            },
         );
+    }
 
-        let job_task = tokio::spawn(async move {
-            TaskScheduler::run_job_loop(
-                name,
-                schedule,
-                task.command,
-                task.args,
-                task.timeout,
-                task.webhook_url,
-                task.cwd,
-                task.env,
-            )
-            .await;
-
-            handle_ref_for_job.lock().unwrap().take();
-        });
+    /// Returns a future that retries spooled notifications against the
+    /// current task definitions, or `None` if there is no spool.
+    pub fn retry_spooled_notifications(&self) -> Option<impl Future<Output = ()> + Send + 'static> {
+        let spool = self.runner.spool.clone()?;
+        let tasks: HashMap<String, Task> = self
+            .jobs
+            .iter()
+            .map(|(name, job)| (name.clone(), job.task.clone()))
+            .collect();
+        Some(async move { spool.retry(&tasks).await })
+    }
 
-        *handle_ref.lock().unwrap() = Some(job_task);
+    /// Returns a snapshot of every task, sorted by name.
+    pub fn status(&self) -> Vec<TaskStatus> {
+        let mut statuses: Vec<TaskStatus> = self
+            .jobs
+            .values()
+            .map(|job| job.status.lock().unwrap().clone())
+            .collect();
+        statuses.sort_by(|a, b| a.name.cmp(&b.name));
+        statuses
     }
 
-    async fn run_job_loop(
-        name: String,
-        schedule: Schedule,
-        command: String,
-        args: Option<Vec<String>>,
-        timeout: Option<u64>,
-        webhook_url: Option<String>,
-        cwd: Option<String>,
-        env: Option<HashMap<String, String>>,
-    ) {
-        let mut job_running = true;
-
-        while job_running {
-            let now = chrono::Local::now();
-
-            if let Some(next_execution) = schedule.upcoming(chrono::Local).next() {
-                let delay = next_execution - now;
-                let duration = delay.to_std().unwrap_or_default();
-
-                sleep(duration).await;
-
-                TaskScheduler::execute_command(
-                    &name,
-                    &command,
-                    args.as_deref().unwrap_or(&[]),
-                    timeout,
-                    webhook_url.as_deref(),
-                    cwd.as_deref(),
-                    env.as_ref(),
-                )
-                .await;
-            } else {
-                warn!(
-                    "[{}] Schedule ended or failed to calculate next time.",
-                    name
-                );
-                job_running = false;
-            }
+    /// Starts a run of `name` right away, subject to its overlap policy.
+    pub async fn trigger(&self, name: &str) -> Result<String, String> {
+        self.send_job_command(name, JobCommand::Trigger).await
+    }
+
+    /// Stops every in-flight run of `name` and drops its queued runs.
+    pub async fn cancel(&self, name: &str) -> Result<String, String> {
+        self.send_job_command(name, JobCommand::Cancel).await
+    }
+
+    /// Stops scheduled fires of `name` until it is resumed. Manual triggers
+    /// still run. The paused state survives config reloads.
+    pub fn pause(&self, name: &str) -> Result<String, String> {
+        self.set_paused(name, true)
+    }
+
+    pub fn resume(&self, name: &str) -> Result<String, String> {
+        self.set_paused(name, false)
+    }
+
+    fn set_paused(&self, name: &str, paused: bool) -> Result<String, String> {
+        let job = self
+            .jobs
+            .get(name)
+            .ok_or_else(|| format!("Task '{}' not found.", name))?;
+        let mut status = job.status.lock().unwrap();
+        let verb = if paused { "paused" } else { "resumed" };
+        if status.paused == paused {
+            return Ok(format!("Task '{}' is already {}.", name, verb));
         }
+        status.paused = paused;
+        info!("[Scheduler] Task '{}' {}.", name, verb);
+        Ok(format!("Task '{}' {}.", name, verb))
     }
 
-    pub async fn execute_command(
+    async fn send_job_command(
+        &self,
         name: &str,
-        command: &str,
-        args: &[String],
-        timeout: Option<u64>,
-        webhook_url: Option<&str>,
-        cwd: Option<&str>,
-        env: Option<&HashMap<String, String>>,
-    ) {
+        make: fn(oneshot::Sender<String>) -> JobCommand,
+    ) -> Result<String, String> {
+        let job = self
+            .jobs
+            .get(name)
+            .ok_or_else(|| format!("Task '{}' not found.", name))?;
+        let (reply, reply_rx) = oneshot::channel();
+        let not_running = || format!("Task '{}' is no longer scheduled.", name);
+        job.commands
+            .send(make(reply))
+            .await
+            .map_err(|_| not_running())?;
+        reply_rx.await.map_err(|_| not_running())
+    }
+}
+
+impl Runner {
+    pub fn new(
+        children: ChildRegistry,
+        history: Option<HistoryStore>,
+        spool: Option<Spool>,
+        stopping: watch::Receiver<Option<time::Instant>>,
+    ) -> Self {
+        Runner {
+            children,
+            history: history.map(Arc::new),
+            spool: spool.map(Arc::new),
+            stopping,
+            last_failed: Arc::default(),
+            alerts: Arc::default(),
+            deliveries: Arc::default(),
+            task_logs: Arc::default(),
+        }
+    }
+
+    /// Opens the task's `log_file`, if it has one, or joins the runs already
+    /// writing to it, and writes the header of this attempt. Failing to open
+    /// it is logged but does not fail the run.
+    fn open_task_log(
+        &self,
+        task: &Task,
+        context: &RunContext,
+        attempt: u32,
+        started_at: &DateTime<Local>,
+    ) -> Option<Arc<Mutex<TaskLog>>> {
+        let path = task.log_file.as_deref()?;
+        let rotation = task.log_rotate.clone().unwrap_or_default();
+
+        let mut task_logs = self.task_logs.lock().unwrap();
+        task_logs.retain(|_, log| log.strong_count() > 0);
+        let log = match task_logs.get(path).and_then(Weak::upgrade) {
+            Some(log) => {
+                log.lock().unwrap().attach(&rotation);
+                log
+            }
+            None => match TaskLog::open(path, &rotation) {
+                Ok(log) => {
+                    let log = Arc::new(Mutex::new(log));
+                    task_logs.insert(path.to_path_buf(), Arc::downgrade(&log));
+                    log
+                }
+                Err(e) => {
+                    warn!(
+                        "[{}] Failed to open log file {}: {}",
+                        task.name,
+                        path.display(),
+                        e
+                    );
+                    return None;
+                }
+            },
+        };
+
+        log.lock().unwrap().write_line(&format!(
+            "=== {} run {} attempt {} ({}) ===",
+            started_at.to_rfc3339(),
+            context.run_id,
+            attempt,
+            context.trigger
+        ));
+        Some(log)
+    }
+
+    /// Runs a single attempt of `task`. A change on `cancel` terminates the
+    /// run the same way a timeout does.
+    pub async fn execute_command(
+        &self,
+        task: &Task,
+        context: &RunContext,
+        attempt: u32,
+        cancel: &mut watch::Receiver<u64>,
+    ) -> RunResult {
+        let name = task.name.as_str();
+        let command = task.command.as_str();
+        let args = task.args.as_deref().unwrap_or(&[]);
+        let timeout = task.timeout;
+        let started_at = Local::now();
+        let started = Instant::now();
+
         info!("[{}] -> Command starting: {} {:?}", name, command, args);
 
         let mut cmd_to_run = Command::new(command);
         cmd_to_run.args(args);
+        cmd_to_run.kill_on_drop(true);
+        cmd_to_run.stdout(Stdio::piped());
+        cmd_to_run.stderr(Stdio::piped());
+        // Each run gets its own process group so that timeouts and shutdown
+        // reach grandchildren started by shells as well.
+        cmd_to_run.process_group(0);
 
-        if let Some(dir) = cwd {
+        if let Some(dir) = task.cwd.as_deref() {
             cmd_to_run.current_dir(dir);
             info!("[{}] CWD set to: {}", name, dir);
         }
 
-        if let Some(envs) = env {
+        if task.login_env {
+            // The login environment is normally captured at startup. For a
+            // task that gained `login_env` on reload, or under `exec`, the
+            // first capture runs the login shell, which must not block the
+            // runtime.
+            let _ = tokio::task::spawn_blocking(utils::login_environment).await;
+        }
+
+        if let Some(base) = base_environment(task) {
+            cmd_to_run.env_clear();
+            cmd_to_run.envs(&base);
+            info!(
+                "[{}] Base environment replaced ({} variables)",
+                name,
+                base.len()
+            );
+        }
+
+        if let Some(envs) = &task.env {
             cmd_to_run.envs(envs);
             let keys: Vec<&str> = envs.keys().map(|k| k.as_str()).collect();
             info!("[{}] Envs set: {:?}", name, keys);
         }
 
-        let child = match cmd_to_run.spawn() {
+        cmd_to_run.envs(context.env_vars(name, attempt, &started_at));
+
+        let task_log = self.open_task_log(task, context, attempt, &started_at);
+
+        let mut child = match cmd_to_run.spawn() {
             Ok(c) => c,
             Err(e) => {
                 error!("[{}] -> Failed to spawn command '{}': {}", name, command, e);
-                return;
+                let result = RunResult {
+                    outcome: RunOutcome::SpawnFailed(e.to_string()),
+                    started_at,
+                    ended_at: Local::now(),
+                    duration: started.elapsed(),
+                    stdout: String::new(),
+                    stderr: e.to_string(),
+                };
+                close_task_log(task_log, context, &result);
+                return result;
             }
         };
         let child_pid = child.id();
+        let mut child_guard = child_pid.map(|pid| self.children.register(pid, name));
 
-        let execution_future = child.wait_with_output();
+        let stdout_reader = child
+            .stdout
+            .take()
+            .map(|out| OutputReader::spawn(out, task, "stdout", task_log.clone()));
+        let stderr_reader = child
+            .stderr
+            .take()
+            .map(|err| OutputReader::spawn(err, task, "stderr", task_log.clone()));
 
-        let output_result = if let Some(s) = timeout {
+        if let Some(s) = timeout {
             info!("[{}] Running command with timeout: {}s", name, s);
+        } else {
+            info!("[{}] Running command (no timeout limit)", name);
+        }
 
-            let duration = Duration::from_secs(s);
+        let timeout_elapsed = async {
+            match timeout {
+                Some(s) => sleep(Duration::from_secs(s)).await,
+                None => std::future::pending().await,
+            }
+        };
 
-            match time::timeout(duration, execution_future).await {
-                Ok(result) => result,
-                Err(_) => {
-                    error!(
-                        "[{}] -> Command TIMEOUT after {} seconds. Killing process.",
-                        name, s
-                    );
+        let kill_grace = Duration::from_secs(task.kill_grace.unwrap_or(DEFAULT_KILL_GRACE));
 
-                    if let Some(pid) = child_pid {
-                        let kill_status = tokio::process::Command::new("kill")
-                            .arg("-9")
-                            .arg(pid.to_string())
-                            .status()
-                            .await;
-
-                        match kill_status {
-                            Ok(status) if status.success() => {
-                                error!("[{}] Child process PID {} killed successfully.", name, pid);
-                            }
-                            _ => {
-                                error!("[{}] Failed to kill child process PID {}.", name, pid);
-                            }
-                        }
-                    }
+        let outcome = tokio::select! {
+            result = child.wait() => RunOutcome::from_wait(result),
+            _ = timeout_elapsed => {
+                error!(
+                    "[{}] -> Command TIMEOUT after {} seconds. Sending SIGTERM to process group (kill grace: {}s).",
+                    name,
+                    timeout.unwrap_or_default(),
+                    kill_grace.as_secs()
+                );
+                let killed = terminate_group(name, &mut child, kill_grace).await;
+                RunOutcome::TimedOut { killed }
+            }
+            Ok(()) = cancel.changed() => {
+                // On shutdown the run gets whatever is left of the shutdown
+                // grace period instead of its own kill grace.
+                let kill_grace = match *self.stopping.borrow() {
+                    Some(deadline) => deadline.saturating_duration_since(time::Instant::now()),
+                    None => kill_grace,
+                };
+                warn!(
+                    "[{}] -> Run cancelled. Sending SIGTERM to process group (kill grace: {}s).",
+                    name,
+                    kill_grace.as_secs()
+                );
+                let killed = terminate_group(name, &mut child, kill_grace).await;
+                RunOutcome::Cancelled { killed }
+            }
+        };
 
-                    let io_error = std::io::Error::new(
-                        std::io::ErrorKind::TimedOut,
-                        format!("Command timed out after {} seconds", s),
-                    );
+        if let Some(guard) = child_guard.as_mut() {
+            guard.finished = true;
+        }
+
+        let result = RunResult {
+            outcome,
+            started_at,
+            ended_at: Local::now(),
+            duration: started.elapsed(),
+            stdout: collect_output(stdout_reader).await,
+            stderr: collect_output(stderr_reader).await,
+        };
+        close_task_log(task_log, context, &result);
 
-                    Err(io_error)
+        match &result.outcome {
+            outcome if outcome.is_success() => {
+                info!(
+                    "[{}] -> Command SUCCESS. Status: {} (took {:.2}s)",
+                    name,
+                    outcome,
+                    result.duration.as_secs_f64()
+                );
+            }
+            outcome @ RunOutcome::Cancelled { .. } => {
+                warn!(
+                    "[{}] -> Command CANCELLED. Status: {} (took {:.2}s)",
+                    name,
+                    outcome,
+                    result.duration.as_secs_f64()
+                );
+            }
+            RunOutcome::WaitFailed(e) => {
+                error!(
+                    "[{}] -> Execution error: Failed to run command '{}': {}",
+                    name, command, e
+                );
+            }
+            outcome => {
+                error!(
+                    "[{}] -> Command FAILED. Status: {} (took {:.2}s)",
+                    name,
+                    outcome,
+                    result.duration.as_secs_f64()
+                );
+            }
+        }
+
+        result
+    }
+
+    /// Runs a task, retrying failed attempts according to its retry settings.
+    /// Every attempt is written to the history store. The failure alert is
+    /// only sent once the final attempt has failed, in the background: the
+    /// run is over before it is delivered. Retries stop early once the
+    /// scheduler is shutting down.
+    pub async fn run_task(
+        &self,
+        task: &Task,
+        context: &RunContext,
+        mut cancel: watch::Receiver<u64>,
+    ) -> RunResult {
+        let previous_failed = task.has_notify_targets()
+            && task.notify_events().contains(&NotifyEvent::Recovered)
+            && self.previous_run_failed(&task.name).await;
+        let max_attempts = task.retries.unwrap_or(0).saturating_add(1);
+        let mut stopping = self.stopping.clone();
+        let mut attempt = 1;
+
+        let result = loop {
+            let result = self
+                .execute_command(task, context, attempt, &mut cancel)
+                .await;
+            self.record_history(task, context, attempt, &result);
+
+            if matches!(result.outcome, RunOutcome::Cancelled { .. }) {
+                return result;
+            }
+
+            if result.outcome.is_success() || attempt >= max_attempts || stopping.borrow().is_some()
+            {
+                break result;
+            }
+
+            let delay = task.retry_delay(attempt);
+            warn!(
+                "[{}] Attempt {}/{} failed ({}). Retrying in {}s...",
+                task.name,
+                attempt,
+                max_attempts,
+                result.outcome,
+                delay.as_secs()
+            );
+
+            tokio::select! {
+                _ = sleep(delay) => {}
+                Ok(_) = stopping.wait_for(Option::is_some) => {
+                    warn!("[{}] Shutting down. Abandoning remaining retries.", task.name);
+                    break result;
+                }
+                Ok(()) = cancel.changed() => {
+                    warn!("[{}] Run cancelled. Abandoning remaining retries.", task.name);
+                    return result;
                 }
             }
-        } else {
-            info!("[{}] Running command (no timeout limit)", name);
-            execution_future.await
+
+            attempt += 1;
         };
 
-        match output_result {
-            Ok(output) => {
-                let stdout = String::from_utf8_lossy(&output.stdout);
-                let stderr = String::from_utf8_lossy(&output.stderr);
+        if !result.outcome.is_success() && attempt > 1 {
+            error!(
+                "[{}] -> Giving up after {} attempt(s). Last status: {}",
+                task.name, attempt, result.outcome
+            );
+        }
+        self.last_failed
+            .lock()
+            .unwrap()
+            .insert(task.name.clone(), !result.outcome.is_success());
 
-                if output.status.success() {
-                    info!("[{}] -> Command SUCCESS. Status: {}", name, output.status);
-                    if !stdout.trim().is_empty() {
-                        info!("[{}] -> STDOUT:\n{}", name, stdout.trim());
-                    }
+        if let Some((event, repeats)) = self.throttle_notification(task, &result, previous_failed) {
+            let notification = Notification {
+                repeats,
+                task: task.name.clone(),
+                event,
+                status: result.outcome.to_string(),
+                exit_code: match result.outcome {
+                    RunOutcome::Exited(code) => Some(code),
+                    _ => None,
+                },
+                duration_secs: result.duration.as_secs_f64(),
+                run_id: context.run_id.clone(),
+                trigger: context.trigger,
+                attempts: attempt,
+                scheduled_at: context.scheduled_at,
+                started_at: result.started_at,
+                ended_at: result.ended_at,
+                host: utils::hostname().to_string(),
+                stderr: if result.outcome.is_success() {
+                    String::new()
                 } else {
-                    error!("[{}] -> Command FAILED. Status: {}", name, output.status);
-                    if !stderr.trim().is_empty() {
-                        error!("[{}] -> STDERR:\n{}", name, stderr.trim());
+                    result.stderr.clone()
+                },
+            };
+            self.deliver_in_background(task, notification);
+        }
+
+        result
+    }
+
+    fn deliver_in_background(&self, task: &Task, notification: Notification) {
+        let task = task.clone();
+        let spool = self.spool.clone();
+        let mut deliveries = self.deliveries.lock().unwrap();
+        while deliveries.try_join_next().is_some() {}
+        deliveries.spawn(async move {
+            notify::notify_all(&task, &notification, spool.as_deref()).await;
+        });
+    }
+
+    /// Waits for the notifications that are still being delivered. If this
+    /// future is dropped first, the remaining deliveries are abandoned.
+    pub async fn wait_for_notifications(&self) {
+        let mut deliveries = std::mem::take(&mut *self.deliveries.lock().unwrap());
+        if deliveries.is_empty() {
+            return;
+        }
+        info!(
+            "[Notify] Waiting for {} notification(s) to be delivered...",
+            deliveries.len()
+        );
+        while deliveries.join_next().await.is_some() {}
+    }
+
+    /// Decides whether the run's notification is sent, according to the
+    /// task's `notify_on` and `notify_throttle`. Returns the event to send,
+    /// with the repeat count for a "still failing" digest.
+    fn throttle_notification(
+        &self,
+        task: &Task,
+        result: &RunResult,
+        previous_failed: bool,
+    ) -> Option<(NotifyEvent, Option<u64>)> {
+        if !task.has_notify_targets() {
+            return None;
+        }
+        // A recovery that was rate limited is still announced as one.
+        let previous_failed = previous_failed || self.alerts.recovery_pending(&task.name);
+        let event = run_event(&result.outcome, previous_failed)?;
+        let notify = notify_event(task, &result.outcome, previous_failed);
+        let verdict = self.alerts.check(
+            &task.name,
+            event,
+            notify.is_some(),
+            task.notify_throttle.as_ref(),
+            Local::now(),
+        )?;
+
+        match verdict {
+            Verdict::Send => notify.map(|event| (event, None)),
+            Verdict::Digest(repeats) => notify.map(|event| (event, Some(repeats))),
+            Verdict::Repeated(repeats) => {
+                info!(
+                    "[{}] Notification ({}) suppressed: same result as the previous run (x{}).",
+                    task.name, event, repeats
+                );
+                None
+            }
+            Verdict::RateLimited => {
+                warn!(
+                    "[{}] Notification ({}) suppressed: notify_throttle.max_alerts reached.",
+                    task.name, event
+                );
+                None
+            }
+        }
+    }
+
+    /// Whether the latest finished run of `task_name` failed. The first time
+    /// a task is asked about, the answer comes from the history store, so a
+    /// failure before a daemon restart still counts.
+    async fn previous_run_failed(&self, task_name: &str) -> bool {
+        if let Some(failed) = self.last_failed.lock().unwrap().get(task_name) {
+            return *failed;
+        }
+
+        let failed = match self.history.clone() {
+            Some(history) => {
+                let name = task_name.to_string();
+                tokio::task::spawn_blocking(move || history.last_finished_status(&name))
+                    .await
+                    .ok()
+                    .and_then(Result::ok)
+                    .flatten()
+                    .is_some_and(|status| status != RunStatus::Success)
+            }
+            None => false,
+        };
+        // A run that finished in the meantime knows better.
+        *self
+            .last_failed
+            .lock()
+            .unwrap()
+            .entry(task_name.to_string())
+            .or_insert(failed)
+    }
+
+    fn record_history(&self, task: &Task, context: &RunContext, attempt: u32, result: &RunResult) {
+        if let Some(history) = &self.history {
+            let record = HistoryRecord::new(&task.name, context, attempt, result);
+            if let Err(e) = history.append(&record) {
+                warn!("[{}] Failed to write run history: {}", task.name, e);
+            }
+        }
+    }
+}
+
+/// The event a finished run ended with, before `notify_on` is applied. A
+/// success after a failed run is `recovered`. Cancelled runs have none.
+fn run_event(outcome: &RunOutcome, previous_failed: bool) -> Option<NotifyEvent> {
+    Some(match outcome {
+        outcome if outcome.is_success() && previous_failed => NotifyEvent::Recovered,
+        outcome if outcome.is_success() => NotifyEvent::Success,
+        RunOutcome::TimedOut { .. } => NotifyEvent::Timeout,
+        RunOutcome::SpawnFailed(_) => NotifyEvent::SpawnError,
+        RunOutcome::Cancelled { .. } => return None,
+        _ => NotifyEvent::Failure,
+    })
+}
+
+/// The event a finished run reports, if the task has it enabled. A success
+/// after a failed run is reported as `recovered` when enabled, and as
+/// `success` otherwise. Cancelled runs report nothing.
+fn notify_event(task: &Task, outcome: &RunOutcome, previous_failed: bool) -> Option<NotifyEvent> {
+    let enabled = task.notify_events();
+    let event = match run_event(outcome, previous_failed)? {
+        NotifyEvent::Recovered if !enabled.contains(&NotifyEvent::Recovered) => {
+            NotifyEvent::Success
+        }
+        event => event,
+    };
+    enabled.contains(&event).then_some(event)
+}
+
+/// Drives a single task: waits for each fire time and starts runs according
+/// to the task's overlap policy. Also serves trigger and cancel requests
+/// coming from the control socket.
+struct JobLoop {
+    task: Arc<Task>,
+    runner: Runner,
+    // Runs live in a JoinSet owned by the loop, so aborting the loop also
+    // aborts (and, through kill_on_drop, kills) every in-flight run. When
+    // the task changes, the set is handed to the replacement loop.
+    runs: JoinSet<()>,
+    queued: VecDeque<RunContext>,
+    skipped: u64,
+    status: Arc<Mutex<TaskStatus>>,
+    commands: mpsc::Receiver<JobCommand>,
+    // Bumped to cancel every in-flight run of the task.
+    cancel: watch::Sender<u64>,
+}
+
+/// What `dispatch_run` did with a fire.
+enum Dispatch {
+    Started,
+    Queued,
+    Skipped,
+    Replaced,
+}
+
+impl JobLoop {
+    fn new(
+        task: Arc<Task>,
+        runner: Runner,
+        status: Arc<Mutex<TaskStatus>>,
+        commands: mpsc::Receiver<JobCommand>,
+    ) -> Self {
+        JobLoop {
+            task,
+            runner,
+            runs: JoinSet::new(),
+            queued: VecDeque::new(),
+            skipped: 0,
+            status,
+            commands,
+            cancel: watch::Sender::new(0),
+        }
+    }
+
+    async fn run(
+        mut self,
+        predecessor: Option<oneshot::Receiver<Handover>>,
+        mut retire: oneshot::Receiver<Retire>,
+    ) {
+        if let Some(predecessor) = predecessor {
+            if let Ok(handover) = predecessor.await {
+                self.runs = handover.runs;
+                self.queued = handover.queued;
+                self.cancel = handover.cancel;
+                self.update_counts();
+            }
+        }
+
+        let mut stopping = self.runner.stopping.clone();
+        let mut next_execution = self.task.next_fire_after(&Local::now());
+
+        loop {
+            let Some(next) = next_execution else {
+                warn!(
+                    "[{}] Schedule ended or failed to calculate next time.",
+                    self.task.name
+                );
+                break;
+            };
+            self.status.lock().unwrap().next_run = Some(next);
+            let duration = (next - Local::now()).to_std().unwrap_or_default();
+
+            tokio::select! {
+                _ = sleep(duration) => {
+                    if self.status.lock().unwrap().paused {
+                        info!("[{}] Task is paused. Skipping scheduled run.", self.task.name);
+                    } else {
+                        self.dispatch_run(RunContext::scheduled(next));
                     }
 
-                    if let Some(url) = webhook_url {
-                        let error_msg = format!(
-                            "Command exited with status: {}\nStderr: {}",
-                            output.status,
-                            stderr.trim()
-                        );
-                        TaskScheduler::send_alert(url, name, &error_msg).await;
+                    let after = Local::now().max(next);
+                    next_execution = self.task.next_fire_after(&after);
+                }
+                Some(_) = self.runs.join_next(), if !self.runs.is_empty() => {
+                    self.start_queued_run();
+                }
+                Some(command) = self.commands.recv() => {
+                    self.handle_command(command);
+                }
+                retired = &mut retire => {
+                    match retired {
+                        Ok(Some(successor)) => {
+                            self.hand_over(successor);
+                            return;
+                        }
+                        Ok(None) => {
+                            self.stop("Task removed");
+                            break;
+                        }
+                        Err(_) => {
+                            self.stop("Shutting down");
+                            break;
+                        }
                     }
                 }
+                _ = stopping.wait_for(Option::is_some) => {
+                    self.stop("Shutting down");
+                    break;
+                }
             }
-            Err(e) => {
-                error!(
-                    "[{}] -> Execution error: Failed to run command '{}': {}",
-                    name, command, e
+            self.update_counts();
+        }
+
+        drop(retire);
+        self.status.lock().unwrap().next_run = None;
+        while self.runs.join_next().await.is_some() {
+            self.start_queued_run();
+            self.update_counts();
+        }
+    }
+
+    fn handle_command(&mut self, command: JobCommand) {
+        match command {
+            JobCommand::Trigger(reply) => {
+                info!("[{}] Manual trigger requested.", self.task.name);
+                let message = match self.dispatch_run(RunContext::manual()) {
+                    Dispatch::Started => format!("Task '{}' started.", self.task.name),
+                    Dispatch::Replaced => format!(
+                        "Task '{}' started, replacing the run in progress.",
+                        self.task.name
+                    ),
+                    Dispatch::Queued => format!(
+                        "Task '{}' is still running. Run queued ({} pending).",
+                        self.task.name,
+                        self.queued.len()
+                    ),
+                    Dispatch::Skipped if self.task.overlap == OverlapPolicy::Queue => format!(
+                        "Task '{}' is still running and the queue is full ({} pending). Run skipped.",
+                        self.task.name,
+                        self.queued.len()
+                    ),
+                    Dispatch::Skipped => format!(
+                        "Task '{}' is still running. Run skipped (overlap: skip).",
+                        self.task.name
+                    ),
+                };
+                let _ = reply.send(message);
+            }
+            JobCommand::Cancel(reply) => {
+                let dropped = self.queued.len();
+                self.queued.clear();
+                let running = self.runs.len();
+                if running > 0 {
+                    self.cancel.send_modify(|generation| *generation += 1);
+                }
+                info!(
+                    "[{}] Cancel requested. Cancelling {} run(s), dropping {} queued run(s).",
+                    self.task.name, running, dropped
                 );
+                let _ = reply.send(format!(
+                    "Task '{}': cancelling {} running run(s), dropped {} queued run(s).",
+                    self.task.name, running, dropped
+                ));
             }
         }
     }
 
-    async fn send_alert(webhook_url: &str, task_name: &str, message: &str) {
-        let client = Client::new();
-        let payload = json!({
-            "text": format!("**Chronsync Task Failed** \n\n**Task:** `{}`\n**Error** {}", task_name, message)
+    /// Passes the runs in progress and the queued runs to `successor`.
+    fn hand_over(&mut self, successor: oneshot::Sender<Handover>) {
+        while self.runs.try_join_next().is_some() {}
+        if !self.runs.is_empty() {
+            info!(
+                "[{}] Task changed. {} run(s) in progress continue with the previous definition.",
+                self.task.name,
+                self.runs.len()
+            );
+        }
+        let _ = successor.send(Handover {
+            runs: std::mem::take(&mut self.runs),
+            queued: std::mem::take(&mut self.queued),
+            cancel: std::mem::replace(&mut self.cancel, watch::Sender::new(0)),
         });
+    }
 
-        match client.post(webhook_url).json(&payload).send().await {
-            Ok(res) => {
-                if res.status().is_success() {
-                    info!("[{}] Webhook alert sent successfully.", task_name);
-                } else {
-                    error!(
-                        "[{}] Failed to send webhook. Status: {}",
-                        task_name,
-                        res.status()
+    /// Drops the queued runs and cancels the ones in progress, for a task
+    /// that is no longer scheduled.
+    fn stop(&mut self, reason: &str) {
+        if !self.queued.is_empty() {
+            warn!(
+                "[{}] {}. Dropping {} queued run(s).",
+                self.task.name,
+                reason,
+                self.queued.len()
+            );
+            self.queued.clear();
+        }
+        while self.runs.try_join_next().is_some() {}
+        if !self.runs.is_empty() {
+            warn!(
+                "[{}] {}. Cancelling {} run(s) in progress.",
+                self.task.name,
+                reason,
+                self.runs.len()
+            );
+            self.cancel.send_modify(|generation| *generation += 1);
+        }
+    }
+
+    fn update_counts(&mut self) {
+        while self.runs.try_join_next().is_some() {}
+        let mut status = self.status.lock().unwrap();
+        status.running = self.runs.len();
+        status.queued = self.queued.len();
+    }
+
+    fn start_queued_run(&mut self) {
+        if !self.runs.is_empty() {
+            return;
+        }
+        if let Some(context) = self.queued.pop_front() {
+            info!(
+                "[{}] Previous run finished. Starting queued run ({} still pending).",
+                self.task.name,
+                self.queued.len()
+            );
+            self.spawn_run(context);
+        }
+    }
+
+    fn dispatch_run(&mut self, context: RunContext) -> Dispatch {
+        while self.runs.try_join_next().is_some() {}
+
+        if self.runs.is_empty() {
+            self.skipped = 0;
+            self.spawn_run(context);
+            return Dispatch::Started;
+        }
+
+        match self.task.overlap {
+            OverlapPolicy::Skip => {
+                self.skipped += 1;
+                warn!(
+                    "[{}] Previous run still in progress. Skipping {} run (overlap: skip, {} skipped so far).",
+                    self.task.name, context.trigger, self.skipped
+                );
+                Dispatch::Skipped
+            }
+            OverlapPolicy::Queue => {
+                // A pending scheduled run already stands in for every fire
+                // until it starts.
+                let merged = context.trigger == RunTrigger::Schedule
+                    && self
+                        .queued
+                        .iter()
+                        .any(|q| q.trigger == RunTrigger::Schedule);
+                if merged || self.queued.len() >= MAX_QUEUED_RUNS {
+                    self.skipped += 1;
+                    warn!(
+                        "[{}] Previous run still in progress and {} run(s) already queued. Skipping {} run (overlap: queue, {} skipped so far).",
+                        self.task.name,
+                        self.queued.len(),
+                        context.trigger,
+                        self.skipped
                     );
+                    return Dispatch::Skipped;
                 }
+                self.queued.push_back(context);
+                warn!(
+                    "[{}] Previous run still in progress. Queued run (overlap: queue, {} pending).",
+                    self.task.name,
+                    self.queued.len()
+                );
+                Dispatch::Queued
             }
-            Err(e) => {
-                error!("[{}] Failed to send webhook: {}", task_name, e);
+            OverlapPolicy::Replace => {
+                warn!(
+                    "[{}] Previous run still in progress. Cancelling it to start the new run (overlap: replace).",
+                    self.task.name
+                );
+                // The new run subscribes after the bump, so only the runs
+                // already in progress see it.
+                self.cancel.send_modify(|generation| *generation += 1);
+                self.spawn_run(context);
+                Dispatch::Replaced
+            }
+            OverlapPolicy::Allow => {
+                info!(
+                    "[{}] Starting run alongside {} run(s) still in progress (overlap: allow).",
+                    self.task.name,
+                    self.runs.len()
+                );
+                self.spawn_run(context);
+                Dispatch::Started
+            }
+        }
+    }
+
+    fn spawn_run(&mut self, context: RunContext) {
+        let task = self.task.clone();
+        let runner = self.runner.clone();
+        let status = self.status.clone();
+        let cancel = self.cancel.subscribe();
+        self.runs.spawn(async move {
+            let result = runner.run_task(&task, &context, cancel).await;
+            let mut status = status.lock().unwrap();
+            status.last_started_at = Some(result.started_at);
+            status.last_outcome = Some(result.outcome.to_string());
+            status.last_success = Some(result.outcome.is_success());
+        });
+        self.status.lock().unwrap().running = self.runs.len();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_from(json_data: &str) -> Config {
+        serde_json::from_str(json_data).expect("Should deserialize test config")
+    }
+
+    #[tokio::test]
+    async fn test_reload_only_restarts_changed_tasks() {
+        let mut scheduler = TaskScheduler::new(None, None);
+        scheduler.reload_tasks(config_from(
+            r#"{"tasks": [
+                {"name": "keep", "cron_schedule": "0 0 0 1 1 *", "command": "true"},
+                {"name": "change", "cron_schedule": "0 0 0 1 1 *", "command": "true"},
+                {"name": "drop", "cron_schedule": "0 0 0 1 1 *", "command": "true"}
+            ]}"#,
+        ));
+
+        let keep_id = scheduler.jobs["keep"].handle.id();
+        let change_id = scheduler.jobs["change"].handle.id();
+
+        scheduler.reload_tasks(config_from(
+            r#"{"tasks": [
+                {"name": "keep", "cron_schedule": "0 0 0 1 1 *", "command": "true"},
+                {"name": "change", "cron_schedule": "0 0 0 1 1 *", "command": "false"},
+                {"name": "new", "cron_schedule": "0 0 0 1 1 *", "command": "true"}
+            ]}"#,
+        ));
+
+        assert_eq!(scheduler.jobs.len(), 3);
+        assert_eq!(scheduler.jobs["keep"].handle.id(), keep_id);
+        assert_ne!(scheduler.jobs["change"].handle.id(), change_id);
+        assert_eq!(scheduler.jobs["change"].task.command, "false");
+        assert!(scheduler.jobs.contains_key("new"));
+        assert!(!scheduler.jobs.contains_key("drop"));
+    }
+
+    /// Polls `history` until it holds at least `count` records.
+    async fn wait_for_records(history: &HistoryStore, count: usize) -> Vec<HistoryRecord> {
+        for _ in 0..100 {
+            let records = history.load().unwrap_or_default();
+            if records.len() >= count {
+                return records;
             }
+            sleep(Duration::from_millis(100)).await;
         }
+        panic!("expected {} history record(s)", count);
+    }
+
+    #[tokio::test]
+    async fn test_replace_cancels_previous_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.jsonl");
+        let mut scheduler = TaskScheduler::new(Some(HistoryStore::new(path.clone())), None);
+        scheduler.reload_tasks(config_from(
+            r#"{"tasks": [{"name": "long", "cron_schedule": "0 0 0 1 1 *", "command": "sleep",
+                "args": ["30"], "overlap": "replace", "kill_grace": 1}]}"#,
+        ));
+
+        scheduler.trigger("long").await.unwrap();
+        sleep(Duration::from_millis(200)).await;
+        let message = scheduler.trigger("long").await.unwrap();
+        assert!(message.contains("replacing"), "{}", message);
+
+        let records = wait_for_records(&HistoryStore::new(path), 1).await;
+        assert_eq!(records[0].status, RunStatus::Cancelled);
+        assert_eq!(scheduler.status()[0].running, 1);
+        scheduler.shutdown(Duration::from_secs(5)).await;
+    }
+
+    #[tokio::test]
+    async fn test_changed_task_keeps_run_in_progress() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.jsonl");
+        let mut scheduler = TaskScheduler::new(Some(HistoryStore::new(path.clone())), None);
+        let config = |message: &str| {
+            config_from(&format!(
+                r#"{{"tasks": [{{"name": "backup", "cron_schedule": "0 0 0 1 1 *",
+                    "command": "sh", "args": ["-c", "sleep 1; echo {}"]}}]}}"#,
+                message
+            ))
+        };
+        scheduler.reload_tasks(config("OLD"));
+        scheduler.trigger("backup").await.unwrap();
+        sleep(Duration::from_millis(200)).await;
+
+        scheduler.reload_tasks(config("NEW"));
+        let message = scheduler.trigger("backup").await.unwrap();
+        assert!(message.contains("Run skipped"), "{}", message);
+
+        let history = HistoryStore::new(path);
+        let records = wait_for_records(&history, 1).await;
+        assert_eq!(records[0].status, RunStatus::Success);
+        assert_eq!(records[0].stdout, "OLD\n");
+
+        // Runs of a removed task are cancelled, not killed unrecorded.
+        scheduler.trigger("backup").await.unwrap();
+        sleep(Duration::from_millis(200)).await;
+        scheduler.reload_tasks(config_from(r#"{"tasks": []}"#));
+        let records = wait_for_records(&history, 2).await;
+        assert_eq!(records[1].status, RunStatus::Cancelled);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_cancels_runs_in_progress() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.jsonl");
+        let mut scheduler = TaskScheduler::new(Some(HistoryStore::new(path.clone())), None);
+        scheduler.reload_tasks(config_from(
+            r#"{"tasks": [{"name": "long", "cron_schedule": "0 0 0 1 1 *", "command": "sleep",
+                "args": ["30"]}]}"#,
+        ));
+        scheduler.trigger("long").await.unwrap();
+        sleep(Duration::from_millis(200)).await;
+
+        scheduler.shutdown(Duration::from_secs(5)).await;
+
+        let records = HistoryStore::new(path).load().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].status, RunStatus::Cancelled);
+        assert!(!scheduler.runner.previous_run_failed("long").await);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_grace_overrides_kill_grace() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.jsonl");
+        let mut scheduler = TaskScheduler::new(Some(HistoryStore::new(path.clone())), None);
+        scheduler.reload_tasks(config_from(
+            r#"{"tasks": [{"name": "cleanup", "cron_schedule": "0 0 0 1 1 *", "command": "sh",
+                "args": ["-c", "trap 'sleep 2; echo cleaned; exit 0' TERM; sleep 30 & wait"],
+                "kill_grace": 1}]}"#,
+        ));
+        scheduler.trigger("cleanup").await.unwrap();
+        sleep(Duration::from_millis(200)).await;
+
+        scheduler.shutdown(Duration::from_secs(5)).await;
+
+        let records = HistoryStore::new(path).load().unwrap();
+        assert_eq!(records[0].status, RunStatus::Cancelled);
+        assert_eq!(records[0].stdout, "cleaned\n");
+    }
+
+    #[tokio::test]
+    async fn test_run_does_not_wait_for_notifications() {
+        let (_stop_tx, stopping) = watch::channel(None);
+        let (_cancel_tx, cancel) = watch::channel(0);
+        let runner = Runner::new(ChildRegistry::default(), None, None, stopping);
+        let mut task: Task = serde_json::from_str(
+            r#"{"name": "broken", "cron_schedule": "0 0 0 1 1 *", "command": "false"}"#,
+        )
+        .unwrap();
+        let mut hung = notify::Notifier::new(notify::Target::Command {
+            command: "sleep".to_string(),
+            args: vec!["30".to_string()],
+        });
+        hung.timeout = Some(30);
+        task.notifiers.push(("hung".to_string(), hung));
+
+        let started = Instant::now();
+        let result = runner.run_task(&task, &RunContext::manual(), cancel).await;
+        assert!(!result.outcome.is_success());
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert_eq!(runner.deliveries.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_queue_merges_scheduled_fires_and_is_capped() {
+        let (_stop_tx, stopping) = watch::channel(None);
+        let runner = Runner::new(ChildRegistry::default(), None, None, stopping);
+        let task: Task = serde_json::from_str(
+            r#"{"name": "slow", "cron_schedule": "0 0 0 1 1 *", "command": "sleep",
+                "args": ["30"], "overlap": "queue"}"#,
+        )
+        .unwrap();
+        let status = Arc::new(Mutex::new(TaskStatus::new(&task, false)));
+        let (_commands_tx, commands) = mpsc::channel(1);
+        let mut job_loop = JobLoop::new(Arc::new(task), runner, status, commands);
+
+        assert!(matches!(
+            job_loop.dispatch_run(RunContext::scheduled(Local::now())),
+            Dispatch::Started
+        ));
+        for _ in 0..3 {
+            job_loop.dispatch_run(RunContext::scheduled(Local::now()));
+        }
+        assert_eq!(job_loop.queued.len(), 1);
+
+        for _ in 0..MAX_QUEUED_RUNS + 5 {
+            job_loop.dispatch_run(RunContext::manual());
+        }
+        assert_eq!(job_loop.queued.len(), MAX_QUEUED_RUNS);
+        assert!(matches!(
+            job_loop.dispatch_run(RunContext::manual()),
+            Dispatch::Skipped
+        ));
+        job_loop.stop("Test finished");
+    }
+
+    #[test]
+    fn test_runs_share_the_task_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let (_stop_tx, stopping) = watch::channel(None);
+        let runner = Runner::new(ChildRegistry::default(), None, None, stopping);
+        let task: Task = serde_json::from_str(&format!(
+            r#"{{"name": "logged", "cron_schedule": "0 0 0 1 1 *", "command": "true",
+                "log_file": "{}"}}"#,
+            dir.path().join("logged.log").display()
+        ))
+        .unwrap();
+        let open = || runner.open_task_log(&task, &RunContext::manual(), 1, &Local::now());
+
+        let first = open().unwrap();
+        let second = open().unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+
+        drop((first, second));
+        assert!(open().is_some());
+        assert_eq!(runner.task_logs.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_base_environment_filters_to_inherit_env() {
+        let config = config_from(
+            r#"{"tasks": [
+                {"name": "inherit", "cron_schedule": "0 0 0 1 1 *", "command": "true"},
+                {"name": "clean", "cron_schedule": "0 0 0 1 1 *", "command": "true",
+                 "env_clear": true, "inherit_env": ["PATH", "CHRONSYNC_UNSET_VAR"]}
+            ]}"#,
+        );
+
+        assert!(base_environment(&config.tasks[0]).is_none());
+
+        let clean = base_environment(&config.tasks[1]).unwrap();
+        let keys: Vec<&String> = clean.keys().collect();
+        assert_eq!(keys, ["PATH"]);
+        assert_eq!(clean["PATH"], env::var("PATH").unwrap());
+    }
+
+    #[test]
+    fn test_run_context_env_vars() {
+        let at = Local::now();
+        let context = RunContext::scheduled(at);
+        let vars: HashMap<_, _> = context.env_vars("nightly", 1, &at).into_iter().collect();
+        assert_eq!(vars["CHRONSYNC_TASK_NAME"], "nightly");
+        assert_eq!(vars["CHRONSYNC_TRIGGER"], "schedule");
+        assert_eq!(vars["CHRONSYNC_SCHEDULED_AT"], at.to_rfc3339());
+        assert_eq!(vars["CHRONSYNC_RUN_ID"], context.run_id);
+
+        let retry: HashMap<_, _> = context.env_vars("nightly", 2, &at).into_iter().collect();
+        assert_eq!(retry["CHRONSYNC_TRIGGER"], "retry");
+        assert_eq!(retry["CHRONSYNC_ATTEMPT"], "2");
+        assert_eq!(retry["CHRONSYNC_RUN_ID"], context.run_id);
+
+        assert_ne!(RunContext::manual().run_id, RunContext::manual().run_id);
+    }
+
+    #[test]
+    fn test_output_buffer_keeps_tail() {
+        let mut buffer = OutputBuffer::new(10);
+        buffer.push(b"short");
+        assert_eq!(buffer.contents(), "short");
+
+        buffer.push(b"0123456789abcdef");
+        let contents = buffer.contents();
+        assert!(contents.starts_with("...[truncated]"));
+        assert!(contents.ends_with("6789abcdef"));
+
+        // Never starts in the middle of a multi-byte character.
+        let mut buffer = OutputBuffer::new(4);
+        buffer.push("ああああ".as_bytes());
+        assert_eq!(buffer.contents(), "...[truncated]\nあ");
+    }
+
+    #[tokio::test]
+    async fn test_output_kept_when_pipe_stays_open() {
+        let task = config_from(
+            r#"{"tasks": [{"name": "t", "cron_schedule": "0 0 0 1 1 *", "command": "true",
+                "max_output_bytes": 8}]}"#,
+        )
+        .tasks
+        .remove(0);
+        let (mut writer, reader) = tokio::io::duplex(64);
+        let output = OutputReader::spawn(reader, &task, "stdout", None);
+
+        tokio::io::AsyncWriteExt::write_all(&mut writer, b"first line\npartial")
+            .await
+            .unwrap();
+        // `writer` is still open, so the drain times out.
+        assert_eq!(output.collect().await, "...[truncated]\n\npartial");
+    }
+
+    #[test]
+    fn test_notify_event_follows_notify_on() {
+        let config = config_from(
+            r#"{"tasks": [
+                {"name": "default", "cron_schedule": "0 0 0 1 1 *", "command": "true"},
+                {"name": "all", "cron_schedule": "0 0 0 1 1 *", "command": "true",
+                 "notify_on": ["timeout", "recovered", "success"]}
+            ]}"#,
+        );
+        let (default, all) = (&config.tasks[0], &config.tasks[1]);
+        let ok = RunOutcome::Exited(0);
+        let failed = RunOutcome::Exited(1);
+        let timed_out = RunOutcome::TimedOut { killed: false };
+        let spawn = RunOutcome::SpawnFailed("not found".to_string());
+
+        assert_eq!(
+            notify_event(default, &failed, false),
+            Some(NotifyEvent::Failure)
+        );
+        assert_eq!(
+            notify_event(default, &timed_out, false),
+            Some(NotifyEvent::Timeout)
+        );
+        assert_eq!(
+            notify_event(default, &spawn, false),
+            Some(NotifyEvent::SpawnError)
+        );
+        assert_eq!(notify_event(default, &ok, true), None);
+        let cancelled = RunOutcome::Cancelled { killed: false };
+        assert_eq!(notify_event(default, &cancelled, false), None);
+
+        assert_eq!(notify_event(all, &failed, false), None);
+        assert_eq!(
+            notify_event(all, &timed_out, false),
+            Some(NotifyEvent::Timeout)
+        );
+        assert_eq!(notify_event(all, &ok, true), Some(NotifyEvent::Recovered));
+        assert_eq!(notify_event(all, &ok, false), Some(NotifyEvent::Success));
     }
 }
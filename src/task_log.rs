@@ -0,0 +1,181 @@
+use crate::config::LogRotation;
+use chrono::{DateTime, Local, NaiveDate};
+use log::warn;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// The output file of a task, written to by every run of it. The file is
+/// rotated to `<file>.1`, `<file>.2`, ... according to its `LogRotation`,
+/// keeping at most `keep` rotated files.
+pub struct TaskLog {
+    path: PathBuf,
+    rotation: LogRotation,
+    file: File,
+    size: u64,
+    opened_on: NaiveDate,
+    failed: bool,
+}
+
+impl TaskLog {
+    /// Opens `path` for appending, creating missing parent directories and
+    /// rotating the existing file first if it is due.
+    pub fn open(path: &Path, rotation: &LogRotation) -> io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let today = Local::now().date_naive();
+        if let Ok(metadata) = fs::metadata(path) {
+            let modified_on = metadata
+                .modified()
+                .map(|t| DateTime::<Local>::from(t).date_naive())
+                .unwrap_or(today);
+            let too_big = rotation.max_bytes.is_some_and(|max| metadata.len() >= max);
+            if too_big || (rotation.daily && modified_on < today) {
+                rotate(path, rotation.keep)?;
+            }
+        }
+
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+        Ok(TaskLog {
+            path: path.to_path_buf(),
+            rotation: rotation.clone(),
+            file,
+            size,
+            opened_on: today,
+            failed: false,
+        })
+    }
+
+    /// Prepares the log for another run: takes over `rotation`, which may
+    /// have changed on reload, and writes again after an earlier error.
+    pub fn attach(&mut self, rotation: &LogRotation) {
+        self.rotation = rotation.clone();
+        self.failed = false;
+    }
+
+    /// Appends `line` and a newline, rotating first if the line would push
+    /// the file over `max_bytes` or the day has changed. After the first
+    /// error, a warning is logged and further writes are dropped.
+    pub fn write_line(&mut self, line: &str) {
+        if self.failed {
+            return;
+        }
+        if let Err(e) = self.try_write_line(line) {
+            warn!(
+                "[TaskLog] Failed to write {}: {}. Further output of this run is not written.",
+                self.path.display(),
+                e
+            );
+            self.failed = true;
+        }
+    }
+
+    fn try_write_line(&mut self, line: &str) -> io::Result<()> {
+        let len = line.len() as u64 + 1;
+        let today = Local::now().date_naive();
+        let too_big = self
+            .rotation
+            .max_bytes
+            .is_some_and(|max| self.size > 0 && self.size + len > max);
+        if too_big || (self.rotation.daily && self.opened_on < today) {
+            rotate(&self.path, self.rotation.keep)?;
+            self.file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?;
+            self.size = 0;
+            self.opened_on = today;
+        }
+
+        writeln!(self.file, "{}", line)?;
+        self.size += len;
+        Ok(())
+    }
+}
+
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", index));
+    PathBuf::from(name)
+}
+
+/// Shifts `path` to `path.1`, `path.1` to `path.2` and so on, then removes
+/// rotated files beyond `keep`. With `keep` = 0 the file is just removed.
+fn rotate(path: &Path, keep: usize) -> io::Result<()> {
+    if keep == 0 {
+        fs::remove_file(path)?;
+    } else {
+        for index in (1..keep).rev() {
+            let from = rotated_path(path, index);
+            if from.exists() {
+                fs::rename(&from, rotated_path(path, index + 1))?;
+            }
+        }
+        fs::rename(path, rotated_path(path, 1))?;
+    }
+
+    // Files left over from a larger `keep`.
+    let mut index = keep + 1;
+    while fs::remove_file(rotated_path(path, index)).is_ok() {
+        index += 1;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+
+    fn rotation(max_bytes: Option<u64>, daily: bool, keep: usize) -> LogRotation {
+        LogRotation {
+            max_bytes,
+            daily,
+            keep,
+        }
+    }
+
+    #[test]
+    fn test_rotates_by_size_and_keeps_newest_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("logs").join("task.log");
+
+        let mut log = TaskLog::open(&path, &rotation(Some(10), false, 2)).unwrap();
+        for line in ["first", "second", "third", "fourth"] {
+            log.write_line(line);
+        }
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "fourth\n");
+        assert_eq!(
+            fs::read_to_string(rotated_path(&path, 1)).unwrap(),
+            "third\n"
+        );
+        assert_eq!(
+            fs::read_to_string(rotated_path(&path, 2)).unwrap(),
+            "second\n"
+        );
+        assert!(!rotated_path(&path, 3).exists());
+    }
+
+    #[test]
+    fn test_rotates_file_from_previous_day_on_open() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("task.log");
+        fs::write(&path, "yesterday\n").unwrap();
+        let file = File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() - Duration::from_secs(2 * 24 * 3600))
+            .unwrap();
+
+        let mut log = TaskLog::open(&path, &rotation(None, true, 1)).unwrap();
+        log.write_line("today");
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "today\n");
+        assert_eq!(
+            fs::read_to_string(rotated_path(&path, 1)).unwrap(),
+            "yesterday\n"
+        );
+    }
+}
@@ -0,0 +1,272 @@
+use crate::config::{NotifyEvent, NotifyThrottle};
+use chrono::{DateTime, Local, TimeDelta};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+/// What happens to the notification of a finished run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    /// Send it as usual.
+    Send,
+    /// Send a "still failing" digest for this many consecutive runs with
+    /// the same event.
+    Digest(u64),
+    /// Drop it; the run ended like the previous one. Carries the number of
+    /// consecutive runs with this event so far.
+    Repeated(u64),
+    /// Drop it; `max_alerts` were already sent within `window`.
+    RateLimited,
+}
+
+/// `secs` as a `TimeDelta`, or `None` if it is too large to represent.
+pub fn seconds(secs: u64) -> Option<TimeDelta> {
+    i64::try_from(secs).ok().and_then(TimeDelta::try_seconds)
+}
+
+#[derive(Debug, Default)]
+struct TaskAlerts {
+    /// The event of the latest run, with `recovered` counted as `success`.
+    state: Option<NotifyEvent>,
+    /// Consecutive runs that ended in `state`.
+    repeats: u64,
+    /// The state the notify targets last heard of: the last one sent, or one
+    /// they are not notified about. Lags behind `state` while a change is
+    /// held back by `max_alerts`.
+    reported: Option<NotifyEvent>,
+    /// When notifications were sent within the last window, oldest first.
+    sent: VecDeque<DateTime<Local>>,
+    last_sent: Option<DateTime<Local>>,
+}
+
+/// Per-task notification state. The runner keeps one for its whole life,
+/// so suppression carries over configuration reloads.
+#[derive(Debug, Default)]
+pub struct AlertThrottle {
+    tasks: Mutex<HashMap<String, TaskAlerts>>,
+}
+
+impl AlertThrottle {
+    /// Records that a run of `task` ended with `event` and decides what
+    /// happens to its notification. Returns `None` when the event is not
+    /// one the task `notify`s about.
+    pub fn check(
+        &self,
+        task: &str,
+        event: NotifyEvent,
+        notify: bool,
+        throttle: Option<&NotifyThrottle>,
+        now: DateTime<Local>,
+    ) -> Option<Verdict> {
+        let mut tasks = self.tasks.lock().unwrap();
+        let alerts = tasks.entry(task.to_string()).or_default();
+
+        let state = match event {
+            NotifyEvent::Recovered => NotifyEvent::Success,
+            event => event,
+        };
+        if alerts.state != Some(state) {
+            alerts.state = Some(state);
+            alerts.repeats = 0;
+        }
+        alerts.repeats += 1;
+
+        if !notify {
+            alerts.reported = Some(state);
+            return None;
+        }
+        let Some(throttle) = throttle else {
+            alerts.reported = Some(state);
+            return Some(Verdict::Send);
+        };
+        // A change that was rate limited stays a change until it is sent.
+        let changed = alerts.reported != Some(state);
+
+        let verdict = if changed || !throttle.on_change {
+            Verdict::Send
+        } else if state != NotifyEvent::Success
+            && throttle.digest_interval.is_some_and(|secs| {
+                // An interval too large to represent never comes due.
+                alerts
+                    .last_sent
+                    .is_none_or(|last| seconds(secs).is_some_and(|interval| now - last >= interval))
+            })
+        {
+            Verdict::Digest(alerts.repeats)
+        } else {
+            return Some(Verdict::Repeated(alerts.repeats));
+        };
+
+        // A window too large to represent never lets an alert expire.
+        if let Some(window_start) =
+            seconds(throttle.window).and_then(|window| now.checked_sub_signed(window))
+        {
+            while alerts.sent.front().is_some_and(|t| *t <= window_start) {
+                alerts.sent.pop_front();
+            }
+        }
+        if throttle
+            .max_alerts
+            .is_some_and(|max| alerts.sent.len() >= max as usize)
+        {
+            return Some(Verdict::RateLimited);
+        }
+
+        alerts.sent.push_back(now);
+        alerts.last_sent = Some(now);
+        alerts.reported = Some(state);
+        Some(verdict)
+    }
+
+    /// Whether the latest run of `task` succeeded but the notify targets
+    /// were last told it failed, because the recovery was rate limited.
+    pub fn recovery_pending(&self, task: &str) -> bool {
+        self.tasks.lock().unwrap().get(task).is_some_and(|alerts| {
+            alerts.state == Some(NotifyEvent::Success)
+                && alerts
+                    .reported
+                    .is_some_and(|reported| reported != NotifyEvent::Success)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn throttle(on_change: bool, max_alerts: Option<u32>, digest: Option<u64>) -> NotifyThrottle {
+        NotifyThrottle {
+            on_change,
+            max_alerts,
+            window: 60,
+            digest_interval: digest,
+        }
+    }
+
+    #[test]
+    fn test_only_state_changes_are_sent() {
+        let alerts = AlertThrottle::default();
+        let throttle = throttle(true, None, None);
+        let start = Local::now();
+        let check = |event, secs| {
+            alerts.check(
+                "flaky",
+                event,
+                true,
+                Some(&throttle),
+                start + TimeDelta::seconds(secs),
+            )
+        };
+
+        assert_eq!(check(NotifyEvent::Failure, 0), Some(Verdict::Send));
+        assert_eq!(check(NotifyEvent::Failure, 1), Some(Verdict::Repeated(2)));
+        assert_eq!(check(NotifyEvent::Failure, 2), Some(Verdict::Repeated(3)));
+        assert_eq!(check(NotifyEvent::Timeout, 3), Some(Verdict::Send));
+        assert_eq!(check(NotifyEvent::Recovered, 4), Some(Verdict::Send));
+        assert_eq!(
+            alerts.check("flaky", NotifyEvent::Success, false, Some(&throttle), start),
+            None
+        );
+        assert_eq!(check(NotifyEvent::Failure, 6), Some(Verdict::Send));
+    }
+
+    #[test]
+    fn test_rate_limit_and_digest() {
+        let alerts = AlertThrottle::default();
+        let throttle = throttle(true, Some(2), Some(30));
+        let start = Local::now();
+        let check = |event, secs| {
+            alerts.check(
+                "flapping",
+                event,
+                true,
+                Some(&throttle),
+                start + TimeDelta::seconds(secs),
+            )
+        };
+
+        assert_eq!(check(NotifyEvent::Failure, 0), Some(Verdict::Send));
+        assert_eq!(check(NotifyEvent::Recovered, 1), Some(Verdict::Send));
+        // The change back to failing is held until the window has room.
+        for secs in 2..60 {
+            assert_eq!(
+                check(NotifyEvent::Failure, secs),
+                Some(Verdict::RateLimited)
+            );
+        }
+        assert_eq!(check(NotifyEvent::Failure, 60), Some(Verdict::Send));
+        assert_eq!(check(NotifyEvent::Failure, 61), Some(Verdict::Repeated(60)));
+        // A digest is due 30s after the last alert.
+        assert_eq!(check(NotifyEvent::Failure, 89), Some(Verdict::Repeated(61)));
+        assert_eq!(check(NotifyEvent::Failure, 90), Some(Verdict::Digest(62)));
+        assert_eq!(check(NotifyEvent::Failure, 91), Some(Verdict::Repeated(63)));
+    }
+
+    #[test]
+    fn test_rate_limited_recovery_is_sent_later() {
+        let alerts = AlertThrottle::default();
+        let throttle = throttle(true, Some(1), None);
+        let start = Local::now();
+        let check = |event, notify, secs| {
+            alerts.check(
+                "flapping",
+                event,
+                notify,
+                Some(&throttle),
+                start + TimeDelta::seconds(secs),
+            )
+        };
+
+        assert_eq!(check(NotifyEvent::Failure, true, 0), Some(Verdict::Send));
+        assert_eq!(
+            check(NotifyEvent::Recovered, true, 1),
+            Some(Verdict::RateLimited)
+        );
+        assert!(alerts.recovery_pending("flapping"));
+        // Later successes are announced as the recovery once there is room.
+        assert_eq!(
+            check(NotifyEvent::Recovered, true, 2),
+            Some(Verdict::RateLimited)
+        );
+        assert_eq!(check(NotifyEvent::Recovered, true, 60), Some(Verdict::Send));
+        assert!(!alerts.recovery_pending("flapping"));
+        assert_eq!(check(NotifyEvent::Success, false, 61), None);
+        assert!(!alerts.recovery_pending("flapping"));
+    }
+
+    #[test]
+    fn test_huge_intervals_do_not_overflow() {
+        let alerts = AlertThrottle::default();
+        let throttle = NotifyThrottle {
+            on_change: true,
+            max_alerts: Some(1),
+            window: 10_000_000_000_000_000,
+            digest_interval: Some(u64::MAX),
+        };
+        let start = Local::now();
+        let check = |event, secs| {
+            alerts.check(
+                "task",
+                event,
+                true,
+                Some(&throttle),
+                start + TimeDelta::seconds(secs),
+            )
+        };
+
+        assert_eq!(check(NotifyEvent::Failure, 0), Some(Verdict::Send));
+        assert_eq!(check(NotifyEvent::Failure, 1), Some(Verdict::Repeated(2)));
+        assert_eq!(check(NotifyEvent::Success, 2), Some(Verdict::RateLimited));
+    }
+
+    #[test]
+    fn test_without_throttle_every_event_is_sent() {
+        let alerts = AlertThrottle::default();
+        let now = Local::now();
+        for _ in 0..3 {
+            assert_eq!(
+                alerts.check("task", NotifyEvent::Failure, true, None, now),
+                Some(Verdict::Send)
+            );
+        }
+    }
+}
@@ -1,16 +1,110 @@
 use directories::UserDirs;
+use log::{info, warn};
+use std::collections::HashMap;
+use std::env;
 use std::path::PathBuf;
+use std::process::Command;
+use std::sync::OnceLock;
 
+/// Returns the default configuration file. `config.json` is preferred; if it
+/// does not exist, an existing `config.toml`, `config.yaml` or `config.yml`
+/// is used instead.
 pub fn get_config_path() -> Result<PathBuf, String> {
     if let Some(user_dirs) = UserDirs::new() {
-        let home_dir = user_dirs.home_dir();
-        let config_path = home_dir
-            .join(".config")
-            .join("chronsync")
-            .join("config.json");
+        let config_dir = user_dirs.home_dir().join(".config").join("chronsync");
+        let config_path = config_dir.join("config.json");
+
+        if !config_path.exists() {
+            for name in ["config.toml", "config.yaml", "config.yml"] {
+                let candidate = config_dir.join(name);
+                if candidate.exists() {
+                    return Ok(candidate);
+                }
+            }
+        }
 
         return Ok(config_path);
     }
 
     Err("Could not determine user home directory.".to_string())
 }
+
+pub fn get_data_dir() -> Result<PathBuf, String> {
+    if let Some(user_dirs) = UserDirs::new() {
+        let home_dir = user_dirs.home_dir();
+        let data_dir = home_dir.join(".local").join("share").join("chronsync");
+
+        return Ok(data_dir);
+    }
+
+    Err("Could not determine user home directory.".to_string())
+}
+
+pub fn get_history_path() -> Result<PathBuf, String> {
+    Ok(get_data_dir()?.join("history.jsonl"))
+}
+
+/// Directory where notifications that could not be delivered wait for a
+/// retry.
+pub fn get_spool_dir() -> Result<PathBuf, String> {
+    Ok(get_data_dir()?.join("spool"))
+}
+
+pub fn get_socket_path() -> Result<PathBuf, String> {
+    Ok(get_data_dir()?.join("chronsync.sock"))
+}
+
+/// Returns the environment of the user's login shell (`$SHELL -l`). It is
+/// captured on the first call and reused afterwards; `None` if the shell
+/// could not be run.
+pub fn login_environment() -> Option<&'static HashMap<String, String>> {
+    static LOGIN_ENV: OnceLock<Option<HashMap<String, String>>> = OnceLock::new();
+
+    LOGIN_ENV
+        .get_or_init(|| {
+            let shell = env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string());
+            let output = match Command::new(&shell).args(["-l", "-c", "env -0"]).output() {
+                Ok(output) if output.status.success() => output,
+                Ok(output) => {
+                    warn!(
+                        "Failed to capture login environment from {}: {}",
+                        shell, output.status
+                    );
+                    return None;
+                }
+                Err(e) => {
+                    warn!("Failed to capture login environment from {}: {}", shell, e);
+                    return None;
+                }
+            };
+
+            let vars: HashMap<String, String> = String::from_utf8_lossy(&output.stdout)
+                .split('\0')
+                .filter_map(|entry| entry.split_once('='))
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+            info!(
+                "Captured {} variables from login shell {}.",
+                vars.len(),
+                shell
+            );
+            Some(vars)
+        })
+        .as_ref()
+}
+
+/// Returns the name of this machine, or an empty string if it cannot be
+/// determined.
+pub fn hostname() -> &'static str {
+    static HOSTNAME: OnceLock<String> = OnceLock::new();
+
+    HOSTNAME.get_or_init(|| {
+        let mut buf = [0u8; 256];
+        let rc = unsafe { libc::gethostname(buf.as_mut_ptr() as *mut libc::c_char, buf.len()) };
+        if rc != 0 {
+            return String::new();
+        }
+        let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+        String::from_utf8_lossy(&buf[..len]).into_owned()
+    })
+}
@@ -2,16 +2,106 @@
 use log::{error, info, warn};
 use notify::{Config, RecommendedWatcher, RecursiveMode, Watcher};
 // use std::io::{self, Write};
-use std::path::Path;
-use tokio::sync::mpsc;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{mpsc, watch};
 use tokio::time::{sleep, Duration};
 
-pub async fn start_watcher(path: &Path, tx_reload: mpsc::Sender<()>) -> notify::Result<()> {
+/// Works out what to hand to `notify` for the configuration at `path`.
+///
+/// A configuration directory is watched recursively so that adding or
+/// removing a file also triggers a reload. A single file is watched through
+/// its parent directory: editors that save by writing a temp file and
+/// renaming it over the original replace the inode, which would silently end
+/// a watch on the file itself. Events are then filtered down to the file.
+fn watch_target(path: &Path) -> std::io::Result<(PathBuf, RecursiveMode, Option<PathBuf>)> {
+    if path.is_dir() {
+        return Ok((path.canonicalize()?, RecursiveMode::Recursive, None));
+    }
+
+    let parent = match path.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p,
+        _ => Path::new("."),
+    };
+    let parent = parent.canonicalize()?;
+    let file = match path.file_name() {
+        Some(name) => parent.join(name),
+        None => path.to_path_buf(),
+    };
+    Ok((parent, RecursiveMode::NonRecursive, Some(file)))
+}
+
+/// Watches the parent directories of the env files referenced by the
+/// configuration, replacing the previous set.
+fn update_env_file_watches(
+    watcher: &mut RecommendedWatcher,
+    config_dir: &Path,
+    config_mode: RecursiveMode,
+    files: &[PathBuf],
+    targets: &Mutex<HashSet<PathBuf>>,
+    watched_dirs: &mut HashSet<PathBuf>,
+) {
+    let mut new_targets = HashSet::new();
+    let mut new_dirs = HashSet::new();
+    for file in files {
+        match watch_target(file) {
+            Ok((dir, _, Some(target))) => {
+                new_targets.insert(target);
+                new_dirs.insert(dir);
+            }
+            Ok(_) => warn!("[Watcher] env_file {:?} is a directory.", file),
+            Err(e) => warn!("[Watcher] Cannot watch env_file {:?}: {}", file, e),
+        }
+    }
+
+    // Directories the configuration watch already covers need no watch of
+    // their own.
+    new_dirs.retain(|dir| {
+        !(dir == config_dir
+            || (config_mode == RecursiveMode::Recursive && dir.starts_with(config_dir)))
+    });
+
+    for dir in watched_dirs.difference(&new_dirs) {
+        if let Err(e) = watcher.unwatch(dir) {
+            warn!("[Watcher] Failed to stop watching {:?}: {:?}", dir, e);
+        }
+    }
+    for dir in new_dirs.difference(watched_dirs) {
+        if let Err(e) = watcher.watch(dir, RecursiveMode::NonRecursive) {
+            warn!("[Watcher] Failed to watch {:?}: {:?}", dir, e);
+        }
+    }
+
+    *watched_dirs = new_dirs;
+    *targets.lock().unwrap() = new_targets;
+}
+
+/// Watches the configuration at `path`, plus the env files it references as
+/// published on `env_files`, and sends a debounced signal on `tx_reload`
+/// whenever one of them changes.
+pub async fn start_watcher(
+    path: &Path,
+    mut env_files: watch::Receiver<Vec<PathBuf>>,
+    tx_reload: mpsc::Sender<()>,
+) -> notify::Result<()> {
     let (tx_watcher, mut rx_watcher) = mpsc::channel(100);
+    let (watch_path, mode, target) = watch_target(path)?;
+    let env_targets = Arc::new(Mutex::new(HashSet::new()));
 
+    let handler_env_targets = Arc::clone(&env_targets);
     let handler = move |res: notify::Result<notify::Event>| match res {
         Ok(event) => {
-            if event.kind.is_modify() {
+            let relevant_kind =
+                event.kind.is_modify() || event.kind.is_create() || event.kind.is_remove();
+            let relevant_path = match &target {
+                Some(file) => event.paths.iter().any(|p| p == file),
+                None => true,
+            } || {
+                let env_targets = handler_env_targets.lock().unwrap();
+                event.paths.iter().any(|p| env_targets.contains(p))
+            };
+            if relevant_kind && relevant_path {
                 if let Err(e) = tx_watcher.try_send(()) {
                     error!(
                         "[Watcher] Failed to send event to internal channel: {:?}",
@@ -28,13 +118,45 @@ pub async fn start_watcher(path: &Path, tx_reload: mpsc::Sender<()>) -> notify::
         Config::default().with_poll_interval(Duration::from_secs(1)),
     )?;
 
-    watcher.watch(path, RecursiveMode::NonRecursive)?;
+    watcher.watch(&watch_path, mode)?;
 
     info!("[Watcher] File watcher started on: {:?}", path);
 
+    let mut env_dirs = HashSet::new();
+    let files = env_files.borrow_and_update().clone();
+    update_env_file_watches(
+        &mut watcher,
+        &watch_path,
+        mode,
+        &files,
+        &env_targets,
+        &mut env_dirs,
+    );
+    let mut tracking_env_files = true;
+
     loop {
-        if rx_watcher.recv().await.is_none() {
-            break;
+        tokio::select! {
+            received = rx_watcher.recv() => {
+                if received.is_none() {
+                    break;
+                }
+            }
+            changed = env_files.changed(), if tracking_env_files => {
+                if changed.is_err() {
+                    tracking_env_files = false;
+                    continue;
+                }
+                let files = env_files.borrow_and_update().clone();
+                update_env_file_watches(
+                    &mut watcher,
+                    &watch_path,
+                    mode,
+                    &files,
+                    &env_targets,
+                    &mut env_dirs,
+                );
+                continue;
+            }
         }
 
         sleep(Duration::from_millis(1000)).await;
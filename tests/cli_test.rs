@@ -164,3 +164,918 @@ fn test_cwd_and_env() {
         .stdout(predicate::str::contains(format!("CWD={}", temp_dir_path)))
         .stdout(predicate::str::contains("MY_VAR=hello_rust"));
 }
+
+#[test]
+fn test_timeout_kills_process_group() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let pid_file = temp_dir.path().join("grandchild.pid");
+
+    let mut file = NamedTempFile::new().unwrap();
+    let config_json = format!(
+        r#"
+        {{
+             "tasks": [
+             {{
+                  "name": "stubborn",
+                  "cron_schedule": "* * * * * *",
+                  "command": "sh",
+                  "args": ["-c", "trap '' TERM; sleep 30 & echo $! > {}; wait"],
+                  "timeout": 1,
+                  "kill_grace": 1
+              }}
+             ]
+         }}"#,
+        pid_file.display()
+    );
+
+    writeln!(file, "{}", config_json).unwrap();
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_chronsync"));
+    cmd.arg("exec")
+        .arg("stubborn")
+        .arg("--config-path")
+        .arg(file.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("timed out (killed by SIGKILL"));
+
+    // The grandchild may linger as a zombie until init reaps it, so only a
+    // process that is still running counts as a leak.
+    let pid = std::fs::read_to_string(&pid_file).unwrap();
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid.trim())).unwrap_or_default();
+    let state = stat
+        .rsplit(')')
+        .next()
+        .unwrap_or("")
+        .trim_start()
+        .chars()
+        .next();
+    assert!(
+        matches!(state, None | Some('Z') | Some('X')),
+        "Grandchild should have been killed, state: {:?}",
+        state
+    );
+}
+
+#[test]
+fn test_exec_retries_failed_run() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_dir_path = temp_dir.path().to_str().unwrap().to_string();
+
+    let mut file = NamedTempFile::new().unwrap();
+    let config_json = format!(
+        r#"
+        {{
+             "tasks": [
+             {{
+                  "name": "flaky",
+                  "cron_schedule": "* * * * * *",
+                  "command": "sh",
+                  "args": ["-c", "if [ -f marker ]; then echo RETRY_OK; else touch marker; exit 1; fi"],
+                  "cwd": "{}",
+                  "retries": 2,
+                  "retry_delay": 0
+              }}
+             ]
+         }}"#,
+        temp_dir_path
+    );
+
+    writeln!(file, "{}", config_json).unwrap();
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_chronsync"));
+    cmd.arg("exec")
+        .arg("flaky")
+        .arg("--config-path")
+        .arg(file.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Attempt 1/3 failed"))
+        .stdout(predicate::str::contains("RETRY_OK"))
+        .stdout(predicate::str::contains("Giving up").not());
+}
+
+#[test]
+fn test_history_records_exec_runs() {
+    let home = tempfile::tempdir().unwrap();
+
+    let mut file = NamedTempFile::new().unwrap();
+    writeln!(
+        file,
+        r#"
+        {{
+             "tasks": [
+             {{
+                  "name": "recorded_ok",
+                  "cron_schedule": "* * * * * *",
+                  "command": "echo",
+                  "args": ["HISTORY_STDOUT"]
+              }},
+             {{
+                  "name": "recorded_fail",
+                  "cron_schedule": "* * * * * *",
+                  "command": "sh",
+                  "args": ["-c", "exit 3"]
+              }}
+             ]
+         }}"#
+    )
+    .unwrap();
+
+    for task in ["recorded_ok", "recorded_fail"] {
+        Command::new(env!("CARGO_BIN_EXE_chronsync"))
+            .env("HOME", home.path())
+            .arg("exec")
+            .arg(task)
+            .arg("--config-path")
+            .arg(file.path())
+            .assert()
+            .success();
+    }
+
+    Command::new(env!("CARGO_BIN_EXE_chronsync"))
+        .env("HOME", home.path())
+        .arg("history")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("recorded_ok"))
+        .stdout(predicate::str::contains("recorded_fail"));
+
+    Command::new(env!("CARGO_BIN_EXE_chronsync"))
+        .env("HOME", home.path())
+        .arg("history")
+        .arg("--status")
+        .arg("failed")
+        .arg("--json")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#""exit_code": 3"#))
+        .stdout(predicate::str::contains(r#""trigger": "manual""#))
+        .stdout(predicate::str::contains("recorded_ok").not());
+
+    Command::new(env!("CARGO_BIN_EXE_chronsync"))
+        .env("HOME", home.path())
+        .arg("history")
+        .arg("--task")
+        .arg("recorded_ok")
+        .arg("--json")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("HISTORY_STDOUT"));
+}
+
+#[test]
+fn test_control_socket_commands() {
+    let home = tempfile::tempdir().unwrap();
+    let socket = home.path().join("control.sock");
+
+    let mut file = NamedTempFile::new().unwrap();
+    writeln!(
+        file,
+        r#"
+        {{
+             "tasks": [
+             {{
+                  "name": "yearly",
+                  "cron_schedule": "0 0 0 1 1 *",
+                  "command": "echo",
+                  "args": ["TRIGGERED"]
+              }}
+             ]
+         }}"#
+    )
+    .unwrap();
+
+    let mut daemon = std::process::Command::new(env!("CARGO_BIN_EXE_chronsync"))
+        .env("HOME", home.path())
+        .arg("run")
+        .arg("--config-path")
+        .arg(file.path())
+        .arg("--socket")
+        .arg(&socket)
+        .stdout(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+
+    for _ in 0..50 {
+        if socket.exists() {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+
+    let control = |args: &[&str]| {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_chronsync"));
+        cmd.env("HOME", home.path())
+            .args(args)
+            .arg("--socket")
+            .arg(&socket);
+        cmd
+    };
+
+    control(&["pause", "yearly"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Task 'yearly' paused."));
+    control(&["status"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("yearly").and(predicate::str::contains("paused")));
+    control(&["trigger", "yearly"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Task 'yearly' started."));
+    control(&["trigger", "ghost_task"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Task 'ghost_task' not found."));
+
+    std::thread::sleep(std::time::Duration::from_millis(500));
+
+    Command::new(env!("CARGO_BIN_EXE_chronsync"))
+        .env("HOME", home.path())
+        .arg("history")
+        .arg("--task")
+        .arg("yearly")
+        .arg("--json")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("TRIGGERED"));
+
+    daemon.kill().unwrap();
+    daemon.wait().unwrap();
+}
+
+#[test]
+fn test_check_command_with_toml_and_yaml_files() {
+    let mut toml_file = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+    writeln!(
+        toml_file,
+        r#"
+[[tasks]]
+name = "toml_task"
+cron_schedule = "* * * * * *"
+command = "echo"
+args = ["ok"]
+"#
+    )
+    .unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_chronsync"))
+        .arg("check")
+        .arg("--config-path")
+        .arg(toml_file.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Configuration check passed"));
+
+    let mut yaml_file = tempfile::Builder::new().suffix(".yml").tempfile().unwrap();
+    writeln!(
+        yaml_file,
+        r#"
+tasks:
+  - name: yaml_task
+    cron_schedule: "INVALID_CRON"
+    command: echo
+"#
+    )
+    .unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_chronsync"))
+        .arg("check")
+        .arg("--config-path")
+        .arg(yaml_file.path())
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Validation failed: Invalid YAML"));
+}
+
+#[test]
+fn test_init_writes_template_in_requested_format() {
+    let home = tempfile::tempdir().unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_chronsync"))
+        .env("HOME", home.path())
+        .arg("init")
+        .arg("--format")
+        .arg("yaml")
+        .assert()
+        .success();
+
+    let config_path = home.path().join(".config/chronsync/config.yaml");
+    assert!(config_path.exists());
+
+    // The default path falls back to the YAML file when no config.json exists.
+    Command::new(env!("CARGO_BIN_EXE_chronsync"))
+        .env("HOME", home.path())
+        .arg("check")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Configuration check passed"));
+}
+
+#[test]
+fn test_list_shows_task_timezone() {
+    let mut file = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+    writeln!(
+        file,
+        r#"
+timezone = "UTC"
+
+[[tasks]]
+name = "tokyo_morning"
+cron_schedule = "0 0 9 * * *"
+command = "echo"
+timezone = "Asia/Tokyo"
+"#
+    )
+    .unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_chronsync"))
+        .arg("list")
+        .arg("--config-path")
+        .arg(file.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("09:00:00 JST (Asia/Tokyo)"))
+        .stdout(predicate::str::contains("(local)"));
+}
+
+#[test]
+fn test_list_resolved_shows_inherited_defaults() {
+    let mut file = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+    writeln!(
+        file,
+        r#"
+[defaults]
+timeout = 60
+cwd = "/tmp"
+
+[defaults.env]
+SHARED = "default"
+
+[[tasks]]
+name = "inherits"
+cron_schedule = "0 0 9 * * *"
+command = "echo"
+
+[tasks.env]
+OWN = "task"
+"#
+    )
+    .unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_chronsync"))
+        .arg("list")
+        .arg("--resolved")
+        .arg("--config-path")
+        .arg(file.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Timeout: 60s"))
+        .stdout(predicate::str::contains("Working directory: /tmp"))
+        .stdout(predicate::str::contains("SHARED=default"))
+        .stdout(predicate::str::contains("OWN=task"));
+}
+
+#[test]
+fn test_list_merges_config_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("backup.json"),
+        r#"{"tasks": [{"name": "backup", "cron_schedule": "0 0 3 * * *", "command": "echo"}]}"#,
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("reports.toml"),
+        r#"
+[[tasks]]
+name = "reports"
+cron_schedule = "0 0 9 * * *"
+command = "echo"
+"#,
+    )
+    .unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_chronsync"))
+        .arg("list")
+        .arg("--config-path")
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("(2 Tasks)"))
+        .stdout(predicate::str::contains("[backup]"))
+        .stdout(predicate::str::contains("[reports]"));
+
+    std::fs::write(
+        dir.path().join("duplicate.yaml"),
+        "tasks:\n  - name: backup\n    cron_schedule: \"0 0 4 * * *\"\n    command: echo\n",
+    )
+    .unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_chronsync"))
+        .arg("check")
+        .arg("--config-path")
+        .arg(dir.path())
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Duplicate task name 'backup'"))
+        .stdout(predicate::str::contains("duplicate.yaml"));
+}
+
+#[test]
+fn test_check_strict_reports_findings() {
+    let mut file = NamedTempFile::new().unwrap();
+    writeln!(
+        file,
+        r#"
+        {{
+             "tasks": [
+             {{
+                  "name": "clean",
+                  "cron_schedule": "0 0 3 * * *",
+                  "command": "sh"
+              }},
+             {{
+                  "name": "broken",
+                  "cron_schedule": "* * * * * *",
+                  "command": "chronsync-no-such-command",
+                  "timeout": 0
+              }}
+             ]
+         }}"#
+    )
+    .unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_chronsync"))
+        .arg("check")
+        .arg("--strict")
+        .arg("--config-path")
+        .arg(file.path())
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("[broken] command"))
+        .stdout(predicate::str::contains("[broken] timeout is 0"))
+        .stdout(predicate::str::contains("[clean]").not())
+        .stdout(predicate::str::contains("2 errors, 1 warnings"));
+
+    Command::new(env!("CARGO_BIN_EXE_chronsync"))
+        .arg("check")
+        .arg("--strict")
+        .arg("--json")
+        .arg("--config-path")
+        .arg(file.path())
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(r#""severity": "warning""#))
+        .stdout(predicate::str::contains(r#""task": "broken""#));
+
+    // Nothing but the findings may reach stdout, even with --verbose.
+    let output = Command::new(env!("CARGO_BIN_EXE_chronsync"))
+        .arg("--verbose")
+        .arg("check")
+        .arg("--strict")
+        .arg("--json")
+        .arg("--config-path")
+        .arg(file.path())
+        .output()
+        .unwrap();
+    let findings: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("stdout should be pure JSON");
+    assert_eq!(findings.as_array().unwrap().len(), 3);
+}
+
+#[test]
+fn test_check_reports_error_location() {
+    let mut file = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+    writeln!(
+        file,
+        r#"[[tasks]]
+name = "nightly"
+cron_schedule = "not a cron"
+command = "echo"
+"#
+    )
+    .unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_chronsync"))
+        .arg("check")
+        .arg("--config-path")
+        .arg(file.path())
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(":3:17"))
+        .stdout(predicate::str::contains(
+            r#"3 | cron_schedule = "not a cron""#,
+        ))
+        .stdout(predicate::str::contains(
+            "in task 'nightly', field `tasks[0].cron_schedule`",
+        ));
+}
+
+#[test]
+fn test_env_file_is_merged_under_inline_env() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("global.env"),
+        "SHARED=global\nOVERRIDDEN=global\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("task.env"),
+        "export FROM_TASK_FILE='task file'\nOVERRIDDEN=task_file\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("config.json"),
+        r#"
+        {
+             "env_file": "global.env",
+             "tasks": [
+             {
+                  "name": "env_file_test",
+                  "cron_schedule": "* * * * * *",
+                  "command": "sh",
+                  "args": ["-c", "echo S=$SHARED T=$FROM_TASK_FILE O=$OVERRIDDEN I=$INLINE"],
+                  "env_file": ["task.env"],
+                  "env": {"INLINE": "inline"}
+              }
+             ]
+         }"#,
+    )
+    .unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_chronsync"))
+        .arg("exec")
+        .arg("env_file_test")
+        .arg("--config-path")
+        .arg(dir.path().join("config.json"))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "S=global T=task file O=task_file I=inline",
+        ));
+
+    std::fs::remove_file(dir.path().join("task.env")).unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_chronsync"))
+        .arg("check")
+        .arg("--config-path")
+        .arg(dir.path().join("config.json"))
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("task.env"));
+}
+
+#[test]
+fn test_env_clear_keeps_only_inherited_variables() {
+    let mut file = NamedTempFile::new().unwrap();
+    writeln!(
+        file,
+        r#"
+        {{
+             "tasks": [
+             {{
+                  "name": "clean_env",
+                  "cron_schedule": "* * * * * *",
+                  "command": "sh",
+                  "args": ["-c", "echo KEPT=$KEPT_VAR DROPPED=$DROPPED_VAR OWN=$OWN_VAR"],
+                  "env_clear": true,
+                  "inherit_env": ["PATH", "KEPT_VAR"],
+                  "env": {{"OWN_VAR": "own"}}
+              }}
+             ]
+         }}"#
+    )
+    .unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_chronsync"))
+        .env("KEPT_VAR", "kept")
+        .env("DROPPED_VAR", "dropped")
+        .arg("exec")
+        .arg("clean_env")
+        .arg("--config-path")
+        .arg(file.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("KEPT=kept DROPPED= OWN=own"));
+}
+
+#[test]
+fn test_run_context_is_exported_to_jobs() {
+    let mut file = NamedTempFile::new().unwrap();
+    writeln!(
+        file,
+        r#"
+        {{
+             "tasks": [
+             {{
+                  "name": "context_test",
+                  "cron_schedule": "* * * * * *",
+                  "command": "sh",
+                  "args": ["-c", "echo N=$CHRONSYNC_TASK_NAME T=$CHRONSYNC_TRIGGER A=$CHRONSYNC_ATTEMPT S=$CHRONSYNC_SCHEDULED_AT R=$${{CHRONSYNC_RUN_ID:+set}} B=$${{CHRONSYNC_STARTED_AT:+set}} >&2; exit 1"],
+                  "retries": 1,
+                  "retry_delay": 0
+              }}
+             ]
+         }}"#
+    )
+    .unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_chronsync"))
+        .arg("exec")
+        .arg("context_test")
+        .arg("--config-path")
+        .arg(file.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "N=context_test T=manual A=1 S= R=set B=set",
+        ))
+        .stdout(predicate::str::contains(
+            "N=context_test T=retry A=2 S= R=set B=set",
+        ));
+}
+
+#[test]
+fn test_output_is_streamed_and_kept_on_timeout() {
+    let home = tempfile::tempdir().unwrap();
+
+    let mut file = NamedTempFile::new().unwrap();
+    writeln!(
+        file,
+        r#"
+        {{
+             "tasks": [
+             {{
+                  "name": "streamed",
+                  "cron_schedule": "* * * * * *",
+                  "command": "sh",
+                  "args": ["-c", "seq 1 500; echo LAST_LINE; echo ERR_LINE >&2; sleep 10"],
+                  "timeout": 1,
+                  "max_output_bytes": 64
+              }}
+             ]
+         }}"#
+    )
+    .unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_chronsync"))
+        .env("HOME", home.path())
+        .arg("exec")
+        .arg("streamed")
+        .arg("--config-path")
+        .arg(file.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[streamed] stdout: 1\n"))
+        .stdout(predicate::str::contains("[streamed] stdout: LAST_LINE"))
+        .stdout(predicate::str::contains("[streamed] stderr: ERR_LINE"));
+
+    Command::new(env!("CARGO_BIN_EXE_chronsync"))
+        .env("HOME", home.path())
+        .arg("history")
+        .arg("--task")
+        .arg("streamed")
+        .arg("--json")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#""timed_out": true"#))
+        .stdout(predicate::str::contains(r#"...[truncated]\n"#))
+        .stdout(predicate::str::contains(r#"500\nLAST_LINE\n""#))
+        .stdout(predicate::str::contains(r#""stderr": "ERR_LINE\n""#))
+        .stdout(predicate::str::contains(r#"\n1\n"#).not());
+}
+
+#[test]
+fn test_task_output_is_written_to_log_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("config.json");
+    std::fs::write(
+        &config,
+        r#"
+        {
+             "task_log_dir": "logs",
+             "log_rotate": { "max_bytes": 1048576, "keep": 3 },
+             "tasks": [
+             {
+                  "name": "to_dir",
+                  "cron_schedule": "* * * * * *",
+                  "command": "sh",
+                  "args": ["-c", "echo LOGGED_OUT; echo LOGGED_ERR >&2"]
+              },
+             {
+                  "name": "to_file",
+                  "cron_schedule": "* * * * * *",
+                  "command": "echo",
+                  "args": ["OWN_FILE"],
+                  "log_file": "own/output.log"
+              }
+             ]
+         }"#,
+    )
+    .unwrap();
+
+    for task in ["to_dir", "to_file"] {
+        Command::new(env!("CARGO_BIN_EXE_chronsync"))
+            .env("HOME", dir.path())
+            .arg("exec")
+            .arg(task)
+            .arg("--config-path")
+            .arg(&config)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("stdout: LOGGED_OUT").not());
+    }
+
+    let log = std::fs::read_to_string(dir.path().join("logs").join("to_dir.log")).unwrap();
+    let lines: Vec<&str> = log.lines().collect();
+    assert_eq!(lines.len(), 4, "{}", log);
+    assert!(lines[0].starts_with("=== ") && lines[0].ends_with("attempt 1 (manual) ==="));
+    assert!(lines.contains(&"LOGGED_OUT"));
+    assert!(lines.contains(&"[stderr] LOGGED_ERR"));
+    assert!(lines[3].contains("finished: exit code: 0"), "{}", log);
+
+    let own = std::fs::read_to_string(dir.path().join("own").join("output.log")).unwrap();
+    assert!(own.contains("\nOWN_FILE\n"), "{}", own);
+}
+
+/// Accepts a single HTTP request on a local port and returns its URL and a
+/// handle yielding the request as text.
+fn webhook_receiver() -> (String, std::thread::JoinHandle<String>) {
+    use std::io::Read;
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    let handle = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        stream
+            .set_read_timeout(Some(std::time::Duration::from_secs(5)))
+            .unwrap();
+        let mut request = Vec::new();
+        let mut buf = [0u8; 4096];
+        // Read until the JSON body has been closed.
+        while !request.ends_with(b"}") {
+            match stream.read(&mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => request.extend_from_slice(&buf[..n]),
+            }
+        }
+        stream
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+            .unwrap();
+        String::from_utf8_lossy(&request).into_owned()
+    });
+    (url, handle)
+}
+
+#[test]
+fn test_spawn_error_is_notified() {
+    let (url, received) = webhook_receiver();
+
+    let mut file = NamedTempFile::new().unwrap();
+    writeln!(
+        file,
+        r#"
+        {{
+             "tasks": [
+             {{
+                  "name": "missing_binary",
+                  "cron_schedule": "* * * * * *",
+                  "command": "chronsync-no-such-command",
+                  "webhook_url": "{}",
+                  "notify_on": ["spawn_error"]
+              }}
+             ]
+         }}"#,
+        url
+    )
+    .unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_chronsync"))
+        .arg("exec")
+        .arg("missing_binary")
+        .arg("--config-path")
+        .arg(file.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Notification (spawn_error) sent via 'webhook_url'",
+        ));
+
+    let request = received.join().unwrap();
+    assert!(
+        request.contains("Chronsync Task Failed to Start"),
+        "{}",
+        request
+    );
+    assert!(request.contains("missing_binary"), "{}", request);
+}
+
+/// Kills the wrapped daemon when dropped, so a failed assertion does not
+/// leave it running.
+struct KillOnDrop(std::process::Child);
+
+impl Drop for KillOnDrop {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+#[test]
+fn test_throttled_failure_is_not_resent_after_reload() {
+    let home = tempfile::tempdir().unwrap();
+    let socket = home.path().join("control.sock");
+    let config_path = home.path().join("config.json");
+    let alerts_path = home.path().join("alerts.log");
+
+    let config = |version: &str, extra_task: &str| {
+        format!(
+            r#"{{"notifiers": {{"log": {{"type": "command", "command": "sh",
+                  "args": ["-c", "cat >> {}; echo >> {0}"]}}}},
+                "tasks": [{{"name": "flaky", "cron_schedule": "* * * * * *",
+                  "command": "false", "args": ["{}"], "notify": ["log"],
+                  "notify_throttle": {{}}}}{}]}}"#,
+            alerts_path.display(),
+            version,
+            extra_task
+        )
+    };
+    std::fs::write(&config_path, config("v1", "")).unwrap();
+
+    let daemon = std::process::Command::new(env!("CARGO_BIN_EXE_chronsync"))
+        .env("HOME", home.path())
+        .arg("run")
+        .arg("--config-path")
+        .arg(&config_path)
+        .arg("--socket")
+        .arg(&socket)
+        .stdout(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+    let _daemon = KillOnDrop(daemon);
+
+    let alerts = || {
+        std::fs::read_to_string(&alerts_path)
+            .unwrap_or_default()
+            .lines()
+            .filter(|line| line.contains(r#""event":"failure""#))
+            .count()
+    };
+    let failures = || {
+        let output = Command::new(env!("CARGO_BIN_EXE_chronsync"))
+            .env("HOME", home.path())
+            .args(["history", "--task", "flaky", "--limit", "0", "--json"])
+            .output()
+            .unwrap();
+        String::from_utf8_lossy(&output.stdout)
+            .matches(r#""status": "failed""#)
+            .count()
+    };
+    let status = || {
+        let output = Command::new(env!("CARGO_BIN_EXE_chronsync"))
+            .env("HOME", home.path())
+            .arg("status")
+            .arg("--socket")
+            .arg(&socket)
+            .output()
+            .unwrap();
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+    let wait_until = |done: &dyn Fn() -> bool| {
+        for _ in 0..50 {
+            if done() {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(200));
+        }
+    };
+
+    wait_until(&|| alerts() > 0);
+    assert_eq!(alerts(), 1);
+
+    // Changing the failing task restarts its job loop.
+    std::fs::write(
+        &config_path,
+        config(
+            "v2",
+            r#", {"name": "reloaded", "cron_schedule": "0 0 0 1 1 *", "command": "true"}"#,
+        ),
+    )
+    .unwrap();
+    wait_until(&|| status().contains("reloaded"));
+    assert!(
+        status().contains("reloaded"),
+        "configuration was not reloaded"
+    );
+
+    let failed_before = failures();
+    wait_until(&|| failures() >= failed_before + 2);
+    assert!(failures() >= failed_before + 2);
+    assert_eq!(
+        alerts(),
+        1,
+        "a repeated failure was re-sent after the reload"
+    );
+}
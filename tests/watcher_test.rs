@@ -0,0 +1,204 @@
+use assert_cmd::Command;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Stdio};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+fn task_config(name: &str) -> String {
+    format!(
+        r#"{{"tasks": [{{"name": "{}", "cron_schedule": "0 0 0 1 1 *", "command": "echo"}}]}}"#,
+        name
+    )
+}
+
+/// A daemon running against `config.json` in its own temporary directory.
+struct Daemon {
+    dir: TempDir,
+    socket: PathBuf,
+    child: Child,
+}
+
+impl Daemon {
+    fn start() -> Self {
+        Self::start_with(|dir| fs::write(dir.join("config.json"), task_config("original")).unwrap())
+    }
+
+    /// Lets `setup` populate the directory before the daemon starts. It
+    /// must write a `config.json` with a task named `original`.
+    fn start_with(setup: impl FnOnce(&Path)) -> Self {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("control.sock");
+        let config = dir.path().join("config.json");
+        setup(dir.path());
+
+        let child = std::process::Command::new(env!("CARGO_BIN_EXE_chronsync"))
+            .env("HOME", dir.path())
+            .arg("run")
+            .arg("--config-path")
+            .arg(&config)
+            .arg("--socket")
+            .arg(&socket)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+
+        let daemon = Daemon { dir, socket, child };
+        assert!(
+            daemon.wait_for_task("original"),
+            "daemon did not start with the initial configuration"
+        );
+        daemon
+    }
+
+    fn config(&self) -> PathBuf {
+        self.dir.path().join("config.json")
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.dir.path().join(name)
+    }
+
+    fn chronsync(&self) -> Command {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_chronsync"));
+        cmd.env("HOME", self.dir.path());
+        cmd
+    }
+
+    fn status(&self) -> Option<String> {
+        if !self.socket.exists() {
+            return None;
+        }
+        let output = self
+            .chronsync()
+            .arg("status")
+            .arg("--socket")
+            .arg(&self.socket)
+            .output()
+            .ok()?;
+        Some(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// Polls the daemon until `task` shows up in `status`.
+    fn wait_for_task(&self, task: &str) -> bool {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while Instant::now() < deadline {
+            if self.status().is_some_and(|s| s.contains(task)) {
+                return true;
+            }
+            sleep(Duration::from_millis(200));
+        }
+        false
+    }
+}
+
+impl Drop for Daemon {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Writes `content` to a temp file next to `target` and renames it over
+/// `target`, the way vim and most IDEs save.
+fn atomic_save(target: &Path, tmp: &Path, content: &str) {
+    fs::write(tmp, content).unwrap();
+    fs::rename(tmp, target).unwrap();
+}
+
+#[test]
+fn test_reload_after_in_place_write() {
+    let daemon = Daemon::start();
+
+    fs::write(daemon.config(), task_config("rewritten")).unwrap();
+
+    assert!(daemon.wait_for_task("rewritten"));
+}
+
+#[test]
+fn test_reload_after_atomic_rename_survives_repeated_saves() {
+    let daemon = Daemon::start();
+
+    atomic_save(
+        &daemon.config(),
+        &daemon.path(".config.json.swp"),
+        &task_config("renamed_once"),
+    );
+    assert!(daemon.wait_for_task("renamed_once"));
+
+    // The original inode is gone now; a second save must still be seen.
+    atomic_save(
+        &daemon.config(),
+        &daemon.path(".config.json.swp"),
+        &task_config("renamed_twice"),
+    );
+    assert!(daemon.wait_for_task("renamed_twice"));
+}
+
+#[test]
+fn test_reload_after_remove_then_create() {
+    let daemon = Daemon::start();
+
+    fs::remove_file(daemon.config()).unwrap();
+    fs::write(daemon.config(), task_config("recreated")).unwrap();
+    assert!(daemon.wait_for_task("recreated"));
+
+    fs::write(daemon.config(), task_config("edited_after_recreate")).unwrap();
+    assert!(daemon.wait_for_task("edited_after_recreate"));
+}
+
+#[test]
+fn test_unrelated_files_do_not_affect_watch() {
+    let daemon = Daemon::start();
+
+    fs::write(daemon.path("notes.txt"), "not a config").unwrap();
+    fs::write(daemon.path("other.json"), task_config("unrelated")).unwrap();
+    sleep(Duration::from_millis(1500));
+    assert!(!daemon.status().unwrap().contains("unrelated"));
+
+    fs::write(daemon.config(), task_config("still_watched")).unwrap();
+    assert!(daemon.wait_for_task("still_watched"));
+}
+
+#[test]
+fn test_reload_when_env_file_changes() {
+    let daemon = Daemon::start_with(|dir| {
+        fs::create_dir(dir.join("secrets")).unwrap();
+        fs::write(dir.join("secrets").join("app.env"), "TOKEN=old\n").unwrap();
+        fs::write(
+            dir.join("config.json"),
+            r#"{"tasks": [{"name": "original", "cron_schedule": "0 0 0 1 1 *",
+                "command": "sh", "args": ["-c", "echo TOKEN=$TOKEN"],
+                "env_file": "secrets/app.env"}]}"#,
+        )
+        .unwrap();
+    });
+
+    atomic_save(
+        &daemon.path("secrets").join("app.env"),
+        &daemon.path("secrets").join(".app.env.tmp"),
+        "TOKEN=rotated\n",
+    );
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let mut rotated = false;
+    while !rotated && Instant::now() < deadline {
+        daemon
+            .chronsync()
+            .args(["trigger", "original", "--socket"])
+            .arg(&daemon.socket)
+            .output()
+            .unwrap();
+        sleep(Duration::from_millis(500));
+
+        let output = daemon
+            .chronsync()
+            .args(["history", "--task", "original", "--json"])
+            .output()
+            .unwrap();
+        rotated = String::from_utf8_lossy(&output.stdout).contains("TOKEN=rotated");
+    }
+    assert!(rotated, "env file change was not picked up");
+}